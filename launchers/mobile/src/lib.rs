@@ -1,5 +1,5 @@
 use bevy::prelude::bevy_main;
-use game::LocaleLangs;
+use game::{FileStorage, GameConfig};
 use jni::objects::JObject;
 use jni::*;
 
@@ -20,7 +20,8 @@ fn open_url(url: &str) {
     .unwrap();
 }
 
-fn get_lang() -> game::LocaleLangs {
+/// Language tag of the device, like `es-MX`
+fn get_lang() -> String {
     let ctx = ndk_context::android_context();
     let vm = unsafe { JavaVM::from_raw(ctx.vm().cast()) }.unwrap();
     let mut env = vm.attach_current_thread().unwrap();
@@ -32,24 +33,48 @@ fn get_lang() -> game::LocaleLangs {
     let lang = env
         .call_method(
             lang.l().unwrap(),
-            "getLanguage",
+            "toLanguageTag",
             "()Ljava/lang/String;",
             &[],
         )
         .unwrap();
     let lang = lang.l().unwrap();
     let lang = env.get_string((&lang).into()).unwrap();
-    let lang = lang.to_str().unwrap();
-    let lang = lang.to_lowercase();
+    lang.to_str().unwrap().to_string()
+}
 
-    match lang.as_str() {
-        "es" => LocaleLangs::ES,
-        _ => LocaleLangs::EN,
-    }
+/// Private files directory of the app
+fn get_files_dir() -> String {
+    let ctx = ndk_context::android_context();
+    let vm = unsafe { JavaVM::from_raw(ctx.vm().cast()) }.unwrap();
+    let context = unsafe { JObject::from_raw(ctx.context().cast()) };
+    let mut env = vm.attach_current_thread().unwrap();
+
+    let dir = env
+        .call_method(context, "getFilesDir", "()Ljava/io/File;", &[])
+        .unwrap();
+    let path = env
+        .call_method(
+            dir.l().unwrap(),
+            "getAbsolutePath",
+            "()Ljava/lang/String;",
+            &[],
+        )
+        .unwrap();
+    let path = path.l().unwrap();
+    let path = env.get_string((&path).into()).unwrap();
+    path.to_str().unwrap().to_string()
 }
 
 #[bevy_main]
 fn main() {
     println!("Starting launcher: Mobile");
-    game::app(true, get_lang(), open_url).run();
+    game::app(
+        true,
+        &get_lang(),
+        GameConfig::default(),
+        FileStorage::new(get_files_dir()),
+        open_url,
+    )
+    .run();
 }
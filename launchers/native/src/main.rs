@@ -4,7 +4,35 @@ fn open_url(url: &str) {
     open::that(url).unwrap();
 }
 
+/// Read `--seed <number>` from the command line
+fn get_seed() -> Option<u64> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+    args.next().and_then(|seed| seed.parse().ok())
+}
+
+/// Language of the system, like `es_MX.UTF-8`
+fn get_lang() -> String {
+    std::env::var("LANG").unwrap_or_else(|_| "en-EN".to_string())
+}
+
 fn main() {
     info!("Starting launcher: Native");
-    game::app(true, game::LocaleLangs::EN, open_url).run();
+    let mut config = game::GameConfig::default();
+    if let Some(seed) = get_seed() {
+        config = config.with_seed(seed);
+    }
+    let lang = get_lang();
+    let mut app = match dirs::data_dir() {
+        Some(dir) => {
+            let storage = game::FileStorage::new(dir.join(game::LAUNCHER_TITLE));
+            game::app(true, &lang, config, storage, open_url)
+        }
+        // not the working directory, the settings and scores only last until exit
+        None => {
+            warn!("No data directory on this system, the progress is not saved");
+            let storage = game::MemoryStorage::default();
+            game::app(true, &lang, config, storage, open_url)
+        }
+    };
+    app.run();
 }
@@ -1,4 +1,4 @@
-use game::{LocaleLangs, LAUNCHER_TITLE};
+use game::{GameConfig, Storage, LAUNCHER_TITLE};
 use yew::prelude::*;
 
 fn open_url(url: &str) {
@@ -6,6 +6,29 @@ fn open_url(url: &str) {
     window.location().replace(url).unwrap();
 }
 
+/// Persist the game data on `localStorage`
+struct LocalStorage;
+
+impl LocalStorage {
+    fn get() -> Option<web_sys::Storage> {
+        web_sys::window().and_then(|w| w.local_storage().ok().flatten())
+    }
+}
+
+impl Storage for LocalStorage {
+    fn load(&self, key: &str) -> Option<String> {
+        Self::get()?.get_item(key).ok().flatten()
+    }
+
+    fn save(&self, key: &str, value: &str) {
+        if let Some(storage) = Self::get() {
+            if storage.set_item(key, value).is_err() {
+                log::error!("Unable to save {key}");
+            }
+        }
+    }
+}
+
 fn set_window_title(title: &str) {
     web_sys::window()
         .and_then(|w| w.document())
@@ -13,37 +36,43 @@ fn set_window_title(title: &str) {
         .set_title(title);
 }
 
-fn get_lang() -> LocaleLangs {
-    if let Some(lang) = web_sys::window()
+/// Parts of the url hash, like `#es&seed=404`
+fn get_hash_parts() -> Vec<String> {
+    web_sys::window()
         .and_then(|w| w.document())
         .expect("Unable to get DOM")
         .location()
         .expect("Unable to get Location")
         .hash()
-        .ok()
-        .and_then(|v| if v.is_empty() { None } else { Some(v) })
         .map(|h| {
-            log::info!("The lang from hash is {h}");
-            if h.to_lowercase().contains("es") {
-                LocaleLangs::ES
-            } else {
-                LocaleLangs::EN
-            }
+            h.trim_start_matches('#')
+                .split('&')
+                .filter(|p| !p.is_empty())
+                .map(str::to_string)
+                .collect()
         })
-    {
-        log::info!("From Hash: {lang:?}");
+        .unwrap_or_default()
+}
+
+fn get_seed() -> Option<u64> {
+    get_hash_parts()
+        .iter()
+        .find_map(|p| p.strip_prefix("seed=").and_then(|s| s.parse().ok()))
+}
+
+/// Language tag from the hash, like `#es-MX`, or from the browser
+fn get_lang() -> String {
+    if let Some(lang) = get_hash_parts().into_iter().find(|p| !p.contains('=')) {
+        log::info!("The lang from hash is {lang}");
         return lang;
     }
     let nav_lang = web_sys::window()
         .map(|w| w.navigator())
         .expect("Unable to get navigator")
         .language()
-        .unwrap();
+        .unwrap_or_else(|| "en-EN".to_string());
     log::info!("The lang is {nav_lang}");
-    if nav_lang.to_lowercase().starts_with("es") {
-        return LocaleLangs::ES;
-    }
-    LocaleLangs::EN
+    nav_lang
 }
 
 #[function_component(Root)]
@@ -65,5 +94,9 @@ fn main() {
     yew::Renderer::<Root>::new().render();
     // Start the Bevy App
     log::info!("Starting launcher: WASM");
-    game::app(false, get_lang(), open_url).run();
+    let mut config = GameConfig::default();
+    if let Some(seed) = get_seed() {
+        config = config.with_seed(seed);
+    }
+    game::app(false, &get_lang(), config, LocalStorage, open_url).run();
 }
@@ -0,0 +1,216 @@
+use bevy::{
+    audio::{Volume, VolumeLevel},
+    prelude::*,
+};
+
+use crate::{config::GameConfig, settings::Settings, GameState};
+
+/// Seconds to fade out a track while the next one fades in
+const CROSSFADE_SECS: f32 = 2.;
+/// Distance between the ears, the effects are placed between them
+const EAR_GAP: f32 = 1.;
+
+pub(crate) struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SfxEvent>()
+            .init_resource::<MusicTracks>()
+            .add_systems(
+                Startup,
+                |asset_server: Res<AssetServer>,
+                 config: Res<GameConfig>,
+                 mut tracks: ResMut<MusicTracks>| {
+                    tracks.page = config.music_page.as_ref().map(|p| asset_server.load(p));
+                    tracks.game = config.music_game.as_ref().map(|p| asset_server.load(p));
+                },
+            )
+            .add_systems(
+                Update,
+                (
+                    play_sfx,
+                    switch_music.run_if(state_changed::<GameState>()),
+                    crossfade_music.after(switch_music),
+                ),
+            );
+    }
+}
+
+//
+// Volume categories, each one scaled by the master volume
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AudioBus {
+    Master,
+    Music,
+    Sfx,
+}
+
+//
+// Sound effect to play on the SFX bus
+//
+#[derive(Event, Clone, Debug)]
+pub(crate) struct SfxEvent {
+    pub sound: Handle<AudioSource>,
+    /// Relative to the SFX bus
+    pub volume: f32,
+    /// World position of the source, panned by its screen X
+    pub pos: Option<Vec3>,
+}
+
+impl SfxEvent {
+    pub fn new(sound: Handle<AudioSource>) -> Self {
+        Self {
+            sound,
+            volume: 1.,
+            pos: None,
+        }
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn at(mut self, pos: Vec3) -> Self {
+        self.pos = Some(pos);
+        self
+    }
+}
+
+/// Stereo balance from the horizontal position on the viewport, -1 is left and 1 is right
+fn stereo_pan(x: f32, width: f32) -> f32 {
+    if width <= 0. {
+        return 0.;
+    }
+    (x / width * 2. - 1.).clamp(-1., 1.)
+}
+
+fn play_sfx(
+    mut cmd: Commands,
+    settings: Res<Settings>,
+    camera: Query<(&Camera, &GlobalTransform)>,
+    mut events: EventReader<SfxEvent>,
+) {
+    let camera = camera.get_single().ok();
+    for event in events.iter() {
+        let playback = PlaybackSettings::DESPAWN.with_volume(Volume::Absolute(VolumeLevel::new(
+            settings.output_volume(AudioBus::Sfx) * event.volume,
+        )));
+        let pan = event
+            .pos
+            .zip(camera)
+            .and_then(|(pos, (camera, transform))| {
+                let width = camera.logical_viewport_size()?.x;
+                let pos = camera.world_to_viewport(transform, pos)?;
+                Some(stereo_pan(pos.x, width))
+            });
+        let Some(pan) = pan else {
+            cmd.spawn(AudioBundle {
+                source: event.sound.clone(),
+                settings: playback,
+            });
+            continue;
+        };
+        cmd.spawn(SpatialAudioBundle {
+            source: event.sound.clone(),
+            settings: playback,
+            spatial: SpatialSettings::new(
+                Transform::IDENTITY,
+                EAR_GAP,
+                Vec3::X * pan * EAR_GAP / 2.,
+            ),
+        });
+    }
+}
+
+//
+// Background music, the 404 page and the game have their own track
+//
+#[derive(Resource, Default)]
+struct MusicTracks {
+    page: Option<Handle<AudioSource>>,
+    game: Option<Handle<AudioSource>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MusicKind {
+    Page,
+    Game,
+}
+
+#[derive(Component)]
+pub(crate) struct MusicTrack {
+    pub kind: MusicKind,
+    /// From 0 (silent) to 1 (bus volume)
+    pub fade: f32,
+    fading_in: bool,
+}
+
+fn switch_music(
+    mut cmd: Commands,
+    state: Res<State<GameState>>,
+    tracks: Res<MusicTracks>,
+    mut playing: Query<&mut MusicTrack>,
+) {
+    let kind = match state.get() {
+        GameState::MainPage => MusicKind::Page,
+        _ => MusicKind::Game,
+    };
+    let mut found = false;
+    for mut track in playing.iter_mut() {
+        track.fading_in = track.kind == kind;
+        found |= track.fading_in;
+    }
+    let source = match kind {
+        MusicKind::Page => tracks.page.as_ref(),
+        MusicKind::Game => tracks.game.as_ref(),
+    };
+    let Some(source) = source.filter(|_| !found) else { return; };
+    cmd.spawn((
+        AudioBundle {
+            source: source.clone(),
+            settings: PlaybackSettings::LOOP.with_volume(Volume::Absolute(VolumeLevel::new(0.))),
+        },
+        MusicTrack {
+            kind,
+            fade: 0.,
+            fading_in: true,
+        },
+    ));
+}
+
+fn crossfade_music(
+    mut cmd: Commands,
+    // the real time, the music keeps fading while the game is paused
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut playing: Query<(Entity, &mut MusicTrack, Option<&AudioSink>)>,
+) {
+    let step = time.raw_delta_seconds() / CROSSFADE_SECS;
+    for (entity, mut track, sink) in playing.iter_mut() {
+        let step = if track.fading_in { step } else { -step };
+        track.fade = (track.fade + step).clamp(0., 1.);
+        if track.fade == 0. && !track.fading_in {
+            cmd.entity(entity).despawn_recursive();
+            continue;
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(track.fade * settings.output_volume(AudioBus::Music));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pan_follows_screen_x() {
+        assert_eq!(stereo_pan(0., 800.), -1.);
+        assert_eq!(stereo_pan(400., 800.), 0.);
+        assert_eq!(stereo_pan(800., 800.), 1.);
+        assert_eq!(stereo_pan(1200., 800.), 1.);
+        assert_eq!(stereo_pan(10., 0.), 0.);
+    }
+}
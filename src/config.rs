@@ -0,0 +1,243 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadState, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+//
+// How a round ends
+//
+#[derive(Clone, Copy, Debug, Default, Deserialize, Reflect, PartialEq)]
+pub enum GameMode {
+    /// Never ends
+    #[default]
+    Endless,
+    /// Every bug which escapes cost a life
+    Lives(u32),
+    /// Seconds of the round
+    Timed(f32),
+}
+
+//
+// How fast the game gets harder, see `DifficultyCurve`
+//
+#[derive(Clone, Debug, Default, Deserialize, Reflect, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// Asset with the curves, like `difficulty/custom.difficulty.ron`
+    Custom(String),
+}
+
+//
+// Runtime Game Configuration
+//
+// The values of `.env` are used as defaults when the crate is built,
+// but every launcher can override them or load them from a config asset
+// (`*.config.ron` or `*.config.toml`) without rebuilding the game.
+//
+#[derive(Clone, Debug, Resource, Reflect, Deserialize, TypeUuid)]
+#[uuid = "8b0e5d7c-3f0a-4c9e-9a55-0f6f4d1f7a21"]
+#[serde(default)]
+pub struct GameConfig {
+    /// Seconds the 404 page waits before start the game
+    pub time_wait_to_start: u64,
+    /// Max bugs alive at the same time
+    pub max_bugs_on_screen: usize,
+    /// Url opened by the "Back to Home" button
+    pub home_url: String,
+    /// Asset with the kinds of bugs, see `BugArchetypes`
+    pub bug_archetypes: String,
+    /// Asset with the rules to choose the bugs, see `SpawnRules`
+    pub spawn_rules: String,
+    /// Asset with the available languages, see `LocaleManifest`
+    pub locales: String,
+    /// Asset with the sprite sheet effects, see `EffectDefs`
+    pub effects: String,
+    /// Music of the 404 page, without music when is not set
+    pub music_page: Option<String>,
+    /// Music while playing, crossfaded with the page one
+    pub music_game: Option<String>,
+    /// Seed for the random generator, random when is not set
+    pub seed: Option<u64>,
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    /// Level with the waves of bugs, see `Level`, without level the
+    /// bugs are chosen by the spawn rules until the round ends
+    pub level: Option<String>,
+    /// Config asset which overrides this values once loaded
+    #[serde(skip)]
+    pub asset: Option<String>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            time_wait_to_start: option_env!("TIME_WAIT_TO_START")
+                .and_then(|t| t.parse::<u64>().ok())
+                .unwrap_or(10),
+            max_bugs_on_screen: option_env!("MAX_BUGS_ON_SCREEN")
+                .and_then(|t| t.parse::<usize>().ok())
+                .unwrap_or(30),
+            home_url: option_env!("HOME_URL").unwrap_or("/").to_string(),
+            bug_archetypes: "bugs/default.bugs.ron".to_string(),
+            #[cfg(debug_assertions)]
+            spawn_rules: "rules/debug.rules.ron".to_string(),
+            #[cfg(not(debug_assertions))]
+            spawn_rules: "rules/default.rules.ron".to_string(),
+            locales: "locale/languages.locales.ron".to_string(),
+            effects: "effects/default.effects.ron".to_string(),
+            music_page: Some("audio/music/page.wav".to_string()),
+            music_game: Some("audio/music/game.wav".to_string()),
+            seed: None,
+            mode: GameMode::Endless,
+            difficulty: Difficulty::Normal,
+            level: None,
+            asset: None,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn with_time_wait_to_start(mut self, secs: u64) -> Self {
+        self.time_wait_to_start = secs;
+        self
+    }
+
+    pub fn with_max_bugs_on_screen(mut self, max: usize) -> Self {
+        self.max_bugs_on_screen = max;
+        self
+    }
+
+    pub fn with_home_url(mut self, url: impl Into<String>) -> Self {
+        self.home_url = url.into();
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_mode(mut self, mode: GameMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    pub fn with_level(mut self, path: impl Into<String>) -> Self {
+        self.level = Some(path.into());
+        self
+    }
+
+    /// Load the config from an asset path, like `game.config.ron`
+    pub fn with_asset(mut self, path: impl Into<String>) -> Self {
+        self.asset = Some(path.into());
+        self
+    }
+}
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GameConfig>()
+            .init_resource::<GameConfig>()
+            .add_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            .add_systems(
+                Startup,
+                |mut cmd: Commands, config: Res<GameConfig>, asset_server: Res<AssetServer>| {
+                    if let Some(path) = &config.asset {
+                        let handle: Handle<GameConfig> = asset_server.load(path.as_str());
+                        cmd.insert_resource(GameConfigHandle {
+                            handle,
+                            applied: false,
+                        });
+                    }
+                },
+            )
+            .add_systems(
+                PreUpdate,
+                apply_config_asset.run_if(resource_exists::<GameConfigHandle>()),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct GameConfigHandle {
+    handle: Handle<GameConfig>,
+    applied: bool,
+}
+
+/// The config asset is applied, failed or was not set, so the
+/// values read once on startup are the final ones
+pub(crate) fn config_ready(
+    handle: Option<Res<GameConfigHandle>>,
+    asset_server: Res<AssetServer>,
+) -> bool {
+    handle.map_or(true, |handle| {
+        handle.applied || asset_server.get_load_state(&handle.handle) == LoadState::Failed
+    })
+}
+
+fn apply_config_asset(
+    mut handle: ResMut<GameConfigHandle>,
+    assets: Res<Assets<GameConfig>>,
+    mut events: EventReader<AssetEvent<GameConfig>>,
+    mut config: ResMut<GameConfig>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle: h } | AssetEvent::Modified { handle: h }
+                if *h == handle.handle =>
+            {
+                let Some(loaded) = assets.get(h) else { continue; };
+                let asset = config.asset.take();
+                *config = loaded.clone();
+                config.asset = asset;
+                handle.applied = true;
+                log::info!("Game config loaded: {config:?}");
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let content = std::str::from_utf8(bytes)?;
+            let is_toml = load_context
+                .path()
+                .extension()
+                .map_or(false, |ext| ext == "toml");
+            let config: GameConfig = if is_toml {
+                toml::from_str(content)?
+            } else {
+                ron::from_str(content)?
+            };
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron", "config.toml"]
+    }
+}
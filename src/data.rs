@@ -0,0 +1,53 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{Asset, AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+//
+// Assets described as RON files
+//
+pub trait RonAsset: Asset + DeserializeOwned {
+    /// Extensions handled by the loader, like `bugs.ron`
+    const EXTENSIONS: &'static [&'static str];
+}
+
+pub struct RonAssetLoader<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for RonAssetLoader<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: RonAsset> AssetLoader for RonAssetLoader<T> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let asset = ron::de::from_bytes::<T>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        T::EXTENSIONS
+    }
+}
+
+pub trait RonAssetAppExt {
+    fn add_ron_asset<T: RonAsset>(&mut self) -> &mut Self;
+}
+
+impl RonAssetAppExt for App {
+    fn add_ron_asset<T: RonAsset>(&mut self) -> &mut Self {
+        self.add_asset::<T>()
+            .init_asset_loader::<RonAssetLoader<T>>()
+    }
+}
@@ -1,5 +1,11 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, reflect::TypeUuid, utils::HashMap};
 use bevy_sprite3d::*;
+use serde::Deserialize;
+
+use crate::{
+    config::GameConfig,
+    data::{RonAsset, RonAssetAppExt},
+};
 
 pub struct EffectsPlugin;
 
@@ -7,85 +13,216 @@ impl Plugin for EffectsPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<EffectTypeEvent>()
             .register_type::<Effect>()
-            .insert_resource(ImageAssets::default())
+            .register_type::<EffectDef>()
+            .add_ron_asset::<EffectDefs>()
+            .init_resource::<EffectLibrary>()
             // initially load assets
             .add_systems(
                 Startup,
                 |asset_server: Res<AssetServer>,
-                 mut assets: ResMut<ImageAssets>,
-                 mut texture_atlases: ResMut<Assets<TextureAtlas>>| {
-                    assets.image_bug_1 = asset_server.load("effects/hit.png");
-                    assets.image_bug_2 = asset_server.load("effects/smoke.png");
-
-                    assets.atlas_bug_1 = texture_atlases.add(TextureAtlas::from_grid(
-                        assets.image_bug_1.clone(),
-                        Vec2::new(32.0, 32.0),
-                        3,
-                        1,
-                        None,
-                        None,
-                    ));
-                    assets.atlas_bug_2 = texture_atlases.add(TextureAtlas::from_grid(
-                        assets.image_bug_2.clone(),
-                        Vec2::new(32.0, 32.0),
-                        5,
-                        1,
-                        None,
-                        None,
-                    ));
+                 config: Res<GameConfig>,
+                 mut library: ResMut<EffectLibrary>| {
+                    library.source = asset_server.load(config.effects.as_str());
                 },
             )
-            .add_systems(Update, (spawn_particles, animate_sprite));
+            .add_systems(PreUpdate, load_effects)
+            .add_systems(Update, (spawn_particles, animate_sprite, billboard_effects));
     }
 }
 
+//
+// Sprite sheet effect, described from data
+//
+#[derive(Clone, Debug, Deserialize, Reflect)]
+pub struct EffectDef {
+    pub name: String,
+    /// Sprite sheet, like `effects/hit.png`
+    pub image: String,
+    /// Size in pixels of every frame
+    pub tile_size: (f32, f32),
+    /// Columns and rows of the sprite sheet
+    pub grid: (usize, usize),
+    /// Frames used, all the grid when is not set
+    #[serde(default)]
+    pub frames: Option<usize>,
+    /// Seconds of every frame
+    #[serde(default = "default_frame_duration")]
+    pub frame_duration: f32,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Repeat the frames until the lifetime ends
+    #[serde(default, rename = "loop")]
+    pub looped: bool,
+    /// Seconds alive of a looped effect
+    #[serde(default = "default_lifetime")]
+    pub lifetime: f32,
+    /// Distance to the camera from the position of the event
+    #[serde(default = "default_z_offset")]
+    pub z_offset: f32,
+    /// Always facing the camera
+    #[serde(default)]
+    pub billboard: bool,
+}
+
+impl EffectDef {
+    pub fn frames(&self) -> usize {
+        self.frames.unwrap_or(self.grid.0 * self.grid.1).max(1)
+    }
+}
+
+fn default_frame_duration() -> f32 {
+    0.1
+}
+
+fn default_scale() -> f32 {
+    1.
+}
+
+fn default_lifetime() -> f32 {
+    1.
+}
+
+fn default_z_offset() -> f32 {
+    1.1
+}
+
+#[derive(Debug, Deserialize, Reflect, TypeUuid)]
+#[uuid = "5d2b8e41-7a0c-4f3e-b6d9-8c1a4e2f7b35"]
+pub struct EffectDefs {
+    pub effects: Vec<EffectDef>,
+}
+
+impl RonAsset for EffectDefs {
+    const EXTENSIONS: &'static [&'static str] = &["effects.ron"];
+}
+
+//
+// Loaded effects ready to spawn, by name
+//
 #[derive(Resource, Default)]
-struct ImageAssets {
-    image_bug_1: Handle<Image>,
-    atlas_bug_1: Handle<TextureAtlas>,
-    image_bug_2: Handle<Image>,
-    atlas_bug_2: Handle<TextureAtlas>,
+struct EffectLibrary {
+    source: Handle<EffectDefs>,
+    effects: HashMap<String, (EffectDef, Handle<TextureAtlas>)>,
 }
 
 #[derive(Component, Reflect)]
 #[reflect]
 struct Effect {
     timer: Timer,
+    frames: usize,
+    /// Only for looped effects
+    lifetime: Option<Timer>,
+    billboard: bool,
 }
 
 #[derive(Event)]
 pub enum EffectTypeEvent {
-    Click { pos: Vec3 },
-    Dead { pos: Vec3 },
+    Click {
+        pos: Vec3,
+    },
+    Dead {
+        pos: Vec3,
+    },
+    Miss {
+        pos: Vec3,
+    },
+    /// Effect of the library with the name
+    Named(String, Vec3),
+}
+
+impl EffectTypeEvent {
+    /// The named effect, or the default one when is not set
+    pub fn named_or(name: Option<&String>, default: Self) -> Self {
+        match name {
+            Some(name) => Self::Named(name.clone(), default.pos()),
+            None => default,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            EffectTypeEvent::Click { .. } => "click",
+            EffectTypeEvent::Dead { .. } => "dead",
+            EffectTypeEvent::Miss { .. } => "miss",
+            EffectTypeEvent::Named(name, _) => name,
+        }
+    }
+
+    pub fn pos(&self) -> Vec3 {
+        match self {
+            EffectTypeEvent::Click { pos }
+            | EffectTypeEvent::Dead { pos }
+            | EffectTypeEvent::Miss { pos }
+            | EffectTypeEvent::Named(_, pos) => *pos,
+        }
+    }
+}
+
+fn load_effects(
+    asset_server: Res<AssetServer>,
+    defs: Res<Assets<EffectDefs>>,
+    mut events: EventReader<AssetEvent<EffectDefs>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut library: ResMut<EffectLibrary>,
+) {
+    for event in events.iter() {
+        let (AssetEvent::Created { handle } | AssetEvent::Modified { handle }) = event else {
+            continue;
+        };
+        if *handle != library.source {
+            continue;
+        }
+        let Some(defs) = defs.get(handle) else { continue; };
+        library.effects = defs
+            .effects
+            .iter()
+            .map(|def| {
+                let atlas = texture_atlases.add(TextureAtlas::from_grid(
+                    asset_server.load(def.image.as_str()),
+                    Vec2::new(def.tile_size.0, def.tile_size.1),
+                    def.grid.0,
+                    def.grid.1,
+                    None,
+                    None,
+                ));
+                (def.name.clone(), (def.clone(), atlas))
+            })
+            .collect();
+        log::info!("Loaded {} effects", library.effects.len());
+    }
 }
 
 fn spawn_particles(
     mut cmd: Commands,
-    assets: Res<ImageAssets>,
+    library: Res<EffectLibrary>,
     mut sprite_params: Sprite3dParams,
     mut effects: EventReader<EffectTypeEvent>,
 ) {
     for effect in effects.iter() {
-        let (pos, atlas, scale) = match effect {
-            EffectTypeEvent::Click { pos } => (*pos, assets.atlas_bug_1.clone(), 2.),
-            EffectTypeEvent::Dead { pos } => (*pos, assets.atlas_bug_2.clone(), 3.),
+        let Some((def, atlas)) = library.effects.get(effect.name()) else {
+            log::warn!("Unknown effect: {}", effect.name());
+            continue;
         };
-
-        let pos = Vec3::new(pos.x, pos.y, pos.z + 1.1);
+        let pos = effect.pos() + Vec3::Z * def.z_offset;
 
         cmd.spawn((
             AtlasSprite3d {
-                atlas,
+                atlas: atlas.clone(),
                 pixels_per_metre: 32.,
                 partial_alpha: true,
                 unlit: true,
                 index: 0,
-                transform: Transform::from_translation(pos).with_scale(Vec3::splat(scale)),
+                transform: Transform::from_translation(pos).with_scale(Vec3::splat(def.scale)),
                 ..default()
             }
             .bundle(&mut sprite_params),
             Effect {
-                timer: Timer::from_seconds(0.1, TimerMode::Once),
+                timer: Timer::from_seconds(def.frame_duration, TimerMode::Once),
+                frames: def.frames(),
+                lifetime: def
+                    .looped
+                    .then(|| Timer::from_seconds(def.lifetime, TimerMode::Once)),
+                billboard: def.billboard,
             },
         ));
     }
@@ -97,13 +234,64 @@ fn animate_sprite(
     mut query: Query<(Entity, &mut Effect, &mut AtlasSprite3dComponent)>,
 ) {
     for (entity, mut effect, mut sprite) in query.iter_mut() {
-        if effect.timer.tick(time.delta()).just_finished() {
-            if sprite.index == sprite.atlas.len() - 1 {
+        let delta = time.delta();
+        if let Some(lifetime) = effect.lifetime.as_mut() {
+            if lifetime.tick(delta).finished() {
                 cmd.entity(entity).despawn_recursive();
                 continue;
             }
-            sprite.index += 1;
+        }
+        if effect.timer.tick(delta).just_finished() {
+            if sprite.index + 1 >= effect.frames {
+                if effect.lifetime.is_none() {
+                    cmd.entity(entity).despawn_recursive();
+                    continue;
+                }
+                sprite.index = 0;
+            } else {
+                sprite.index += 1;
+            }
             effect.timer.reset();
         }
     }
 }
+
+fn billboard_effects(
+    camera: Query<&GlobalTransform, With<Camera>>,
+    mut effects: Query<(&Effect, &mut Transform)>,
+) {
+    let Ok(camera) = camera.get_single() else { return; };
+    let rotation = camera.compute_transform().rotation;
+    for (effect, mut transform) in effects.iter_mut() {
+        if effect.billboard && transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_effects_are_valid() {
+        let defs: EffectDefs =
+            ron::from_str(include_str!("../assets/effects/default.effects.ron")).unwrap();
+        for name in ["click", "dead", "miss"] {
+            let def = defs.effects.iter().find(|def| def.name == name).unwrap();
+            assert!(def.frames() <= def.grid.0 * def.grid.1);
+        }
+    }
+
+    #[test]
+    fn optional_fields() {
+        let def: EffectDef = ron::from_str(
+            r#"(name: "spark", image: "effects/spark.png", tile_size: (16.0, 16.0), grid: (4, 2), loop: true)"#,
+        )
+        .unwrap();
+        assert_eq!(def.frames(), 8);
+        assert!(def.looped);
+        assert_eq!(def.scale, 1.);
+        assert!(!def.billboard);
+    }
+}
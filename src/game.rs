@@ -1,24 +1,40 @@
+mod archetype;
+mod boss;
+mod combo;
 mod components;
+mod difficulty;
+mod hit;
+mod miss;
+mod pause;
+mod round;
+mod rules;
 mod systems;
+#[cfg(test)]
+mod tests;
+mod waves;
 
 use bevy::prelude::*;
-use bevy_eventlistener::callbacks::ListenerInput;
-use bevy_mod_picking::prelude::*;
 
+use archetype::*;
+use boss::*;
+use combo::*;
 use components::*;
+use difficulty::*;
+use hit::*;
+use miss::*;
+use pause::*;
+use round::*;
+use rules::*;
 use systems::*;
+use waves::*;
 
-pub use components::ScoreText;
+pub(crate) use combo::ComboEvent;
+pub use components::{RoundText, ScoreText};
+pub(crate) use miss::MissEvent;
+pub(crate) use pause::PauseEvent;
+pub(crate) use round::{RestartEvent, Round};
 
-use crate::GameState;
-
-// Debug Proyect
-#[cfg(debug_assertions)]
-pub const CRAB_SCORES: &[u64] = &[10, 11, 12, 13, 14, 15];
-
-// Release Proyect
-#[cfg(not(debug_assertions))]
-pub const CRAB_SCORES: &[u64] = &[4, 40, 404, 405, 406, 407, 408];
+use crate::{config::GameConfig, data::RonAssetAppExt, GameState};
 
 //
 // Game Plugin
@@ -28,53 +44,84 @@ pub(crate) struct Game;
 impl Plugin for Game {
     fn build(&self, app: &mut App) {
         app.register_type::<BugPathWalk>()
+            .register_type::<BugArchetype>()
             .register_type::<BugData>()
-            .add_event::<BugEntityClickedEvent>();
+            .add_event::<BugEntityClickedEvent>()
+            .add_event::<BugKilledEvent>()
+            .add_event::<BugEscapedEvent>()
+            .add_event::<RestartEvent>()
+            .add_event::<PauseEvent>()
+            .add_event::<ComboEvent>()
+            .add_event::<MissEvent>();
         #[cfg(feature = "inspect")]
         app.register_type::<ScoreTextResource>();
         app.insert_resource(ScoreTextResource(0))
+            .add_ron_asset::<BugArchetypes>()
+            .add_ron_asset::<SpawnRules>()
+            .add_ron_asset::<DifficultyCurve>()
+            .add_ron_asset::<Level>()
+            .init_resource::<DifficultyCurve>()
+            .init_resource::<BugRegistry>()
+            .init_resource::<Combo>()
+            .init_resource::<Waves>()
             .add_systems(
                 Startup,
                 (
                     |mut cmd: Commands,
                      asset_server: Res<AssetServer>,
+                     config: Res<GameConfig>,
+                     mut registry: ResMut<BugRegistry>,
                      mut meshes: ResMut<Assets<Mesh>>,
                      mut materials: ResMut<Assets<StandardMaterial>>| {
+                        registry.source = asset_server.load(config.bug_archetypes.as_str());
+                        registry.rules = asset_server.load(config.spawn_rules.as_str());
+                        cmd.insert_resource(Round::new(config.mode));
+                        cmd.insert_resource(BossAssets::new(&mut meshes, &mut materials));
                         cmd.insert_resource(BugsSpawnTimer {
                             timer: Timer::from_seconds(2., TimerMode::Once),
-                            click_audio: asset_server.load("audio/cursor.wav"),
-                            cube: meshes.add(shape::Box::new(2., 0., 3.).into()),
+                            elapsed: 0.,
                             material: materials.add(Color::ORANGE.with_a(0.).into()),
-                            models: vec![
-                                asset_server.load("animated_3d/spider.glb#Scene0"), //spider
-                                asset_server.load("animated_3d/crab.glb#Scene0"),   //crab
-                            ],
-                            animations: vec![
-                                // Spider Walk
-                                asset_server.load("animated_3d/spider.glb#Animation4"),
-                                // Spider Death
-                                asset_server.load("animated_3d/spider.glb#Animation1"),
-                                // Crab Walk
-                                asset_server.load("animated_3d/crab.glb#Animation1"),
-                                // Crab Death
-                                asset_server.load("animated_3d/crab.glb#Animation0"),
-                            ],
                         })
                     },
+                    spawn_miss_target,
+                    load_miss_sound,
                 ),
             )
-            .add_systems(Update, start_game.run_if(not(in_state(GameState::Game))))
+            .add_systems(PreUpdate, (load_archetypes, load_difficulty))
+            .add_systems(Update, start_game.run_if(in_state(GameState::MainPage)))
             .add_systems(
                 Update,
                 (
-                    factory_bugs,
-                    movement_bugs.after(factory_bugs),
-                    animate_bugs.after(factory_bugs),
-                    kill_detect,
+                    factory_bugs.run_if(not(has_level)),
+                    wave_bugs.run_if(has_level),
+                    wave_banner.after(wave_bugs),
+                    movement_bugs.after(factory_bugs).after(wave_bugs),
+                    animate_bugs.after(factory_bugs).after(boss_update),
+                    combo_update,
+                    hit_detect,
+                    update_hitboxes,
+                    miss_detect.after(combo_update).after(hit_detect),
+                    kill_detect.after(miss_detect).after(hit_detect),
+                    (setup_bosses, boss_update)
+                        .chain()
+                        .after(kill_detect)
+                        .before(movement_bugs),
+                    health_bars.after(movement_bugs),
+                    spawn_combo_text.after(kill_detect),
+                    float_combo_text,
                     score_print,
+                    round_update.after(movement_bugs).after(kill_detect),
+                    round_print.after(round_update),
                 )
                     .run_if(in_state(GameState::Game)),
-            );
+            )
+            .add_systems(
+                Update,
+                (restart_round, focus_pause, action_pause, pause_game).chain(),
+            )
+            .add_systems(Update, remove_wave_banner)
+            .add_systems(OnEnter(GameState::Paused), freeze_time)
+            .add_systems(OnExit(GameState::Paused), unfreeze_time);
     }
 }
 
@@ -88,11 +135,8 @@ pub(crate) struct ScoreTextResource(pub u64);
 #[derive(Resource)]
 struct BugsSpawnTimer {
     timer: Timer,
-    click_audio: Handle<AudioSource>,
-    cube: Handle<Mesh>,
+    elapsed: f32, // seconds since the game started
     material: Handle<StandardMaterial>,
-    models: Vec<Handle<Scene>>,
-    animations: Vec<Handle<AnimationClip>>,
 }
 
 #[derive(Clone, Default)]
@@ -101,28 +145,21 @@ struct BugAnimations {
     death: Handle<AnimationClip>,
 }
 
-impl BugAnimations {
-    pub fn factory(score: u64, asset: &ResMut<BugsSpawnTimer>) -> (Handle<Scene>, Self) {
-        let (model, anims) = if CRAB_SCORES.contains(&score) {
-            (asset.models[1].clone_weak(), &asset.animations[2..=3])
-        } else {
-            (asset.models[0].clone_weak(), &asset.animations[0..=1])
-        };
-        (
-            model,
-            Self {
-                walk: anims[0].clone_weak(),
-                death: anims[1].clone_weak(),
-            },
-        )
-    }
+#[derive(Clone, Default)]
+struct BugSounds {
+    hit: Handle<AudioSource>,
+    death: Option<Handle<AudioSource>>,
 }
 
 #[derive(Event)]
-struct BugEntityClickedEvent(Entity, Option<Vec3>);
+pub(crate) struct BugEntityClickedEvent(Entity, Option<Vec3>);
 
-impl From<ListenerInput<Pointer<Down>>> for BugEntityClickedEvent {
-    fn from(event: ListenerInput<Pointer<Down>>) -> Self {
-        Self(event.target, event.hit.position)
-    }
+#[derive(Event)]
+pub(crate) struct BugKilledEvent {
+    pub archetype: String,
+    pub pos: Vec3,
 }
+
+/// The bug walked the whole path without be killed
+#[derive(Event)]
+pub(crate) struct BugEscapedEvent;
@@ -0,0 +1,164 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use serde::Deserialize;
+
+use crate::{data::RonAsset, path::PathStyle};
+
+use super::{rules::SpawnRules, BugAnimations, BugSounds};
+
+//
+// Bug Archetype, describe a kind of bug from data
+//
+#[derive(Clone, Debug, Deserialize, Reflect)]
+pub struct BugArchetype {
+    pub name: String,
+    /// Scene of the model, like `animated_3d/spider.glb#Scene0`
+    pub model: String,
+    pub walk_animation: String,
+    pub death_animation: String,
+    /// Clicks to kill it, the health of the bosses
+    #[serde(default = "default_max_clicks")]
+    pub max_clicks: u32,
+    pub speed: f32,
+    /// Width and length of the clickable area
+    pub hitbox: (f32, f32),
+    /// Points given when is killed
+    #[serde(default = "default_score")]
+    pub score: u64,
+    #[serde(default)]
+    pub path: PathStyle,
+    /// Sound of every click, the cursor click when is not set
+    #[serde(default)]
+    pub hit_sound: Option<String>,
+    /// Sound played when dies
+    #[serde(default)]
+    pub death_sound: Option<String>,
+    /// Name of the effect of every click, see `EffectDefs`
+    #[serde(default)]
+    pub hit_effect: Option<String>,
+    /// Name of the effect when the body is removed
+    #[serde(default)]
+    pub death_effect: Option<String>,
+    /// Scale of the model
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Health bar and phases, see `BossArchetype`
+    #[serde(default)]
+    pub boss: Option<BossArchetype>,
+}
+
+//
+// Bosses show a health bar and change when the health drops
+//
+#[derive(Clone, Debug, Default, Deserialize, Reflect)]
+pub struct BossArchetype {
+    /// Sorted by `health`, from the highest
+    pub phases: Vec<BossPhase>,
+}
+
+#[derive(Clone, Debug, Deserialize, Reflect, PartialEq)]
+pub struct BossPhase {
+    /// Fraction of the health left when starts, like `0.5`
+    pub health: f32,
+    /// Multiplier of the current speed
+    #[serde(default = "default_phase_speed")]
+    pub speed: f32,
+    /// New path from the position of the boss
+    #[serde(default)]
+    pub path: Option<PathStyle>,
+    /// Bugs spawned on the position of the boss, like `Some(("spider", 3))`
+    #[serde(default)]
+    pub split: Option<(String, u32)>,
+}
+
+const DEFAULT_HIT_SOUND: &str = "audio/cursor.wav";
+
+fn default_max_clicks() -> u32 {
+    1
+}
+
+fn default_scale() -> f32 {
+    1.
+}
+
+fn default_phase_speed() -> f32 {
+    1.
+}
+
+fn default_score() -> u64 {
+    1
+}
+
+#[derive(Debug, Deserialize, Reflect, TypeUuid)]
+#[uuid = "c3f1a0d2-6b7e-4d3a-8f2b-1e9d5a4c7b60"]
+pub struct BugArchetypes {
+    pub bugs: Vec<BugArchetype>,
+}
+
+impl RonAsset for BugArchetypes {
+    const EXTENSIONS: &'static [&'static str] = &["bugs.ron"];
+}
+
+//
+// Loaded bug kinds ready to spawn
+//
+pub(super) struct BugKind {
+    pub(super) archetype: BugArchetype,
+    pub(super) model: Handle<Scene>,
+    pub(super) animations: BugAnimations,
+    pub(super) sounds: BugSounds,
+    pub(super) hitbox: Handle<Mesh>,
+}
+
+#[derive(Resource, Default)]
+pub(super) struct BugRegistry {
+    pub(super) source: Handle<BugArchetypes>,
+    pub(super) rules: Handle<SpawnRules>,
+    pub(super) kinds: Vec<BugKind>,
+}
+
+impl BugRegistry {
+    pub fn get(&self, name: &str) -> Option<&BugKind> {
+        self.kinds.iter().find(|k| k.archetype.name == name)
+    }
+}
+
+pub(super) fn load_archetypes(
+    asset_server: Res<AssetServer>,
+    archetypes: Res<Assets<BugArchetypes>>,
+    mut events: EventReader<AssetEvent<BugArchetypes>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut registry: ResMut<BugRegistry>,
+) {
+    for event in events.iter() {
+        let (AssetEvent::Created { handle } | AssetEvent::Modified { handle }) = event else {
+            continue;
+        };
+        if *handle != registry.source {
+            continue;
+        }
+        let Some(archetypes) = archetypes.get(handle) else { continue; };
+        registry.kinds = archetypes
+            .bugs
+            .iter()
+            .map(|archetype| BugKind {
+                model: asset_server.load(archetype.model.as_str()),
+                animations: BugAnimations {
+                    walk: asset_server.load(archetype.walk_animation.as_str()),
+                    death: asset_server.load(archetype.death_animation.as_str()),
+                },
+                sounds: BugSounds {
+                    hit: asset_server
+                        .load(archetype.hit_sound.as_deref().unwrap_or(DEFAULT_HIT_SOUND)),
+                    death: archetype
+                        .death_sound
+                        .as_ref()
+                        .map(|path| asset_server.load(path.as_str())),
+                },
+                hitbox: meshes
+                    .add(shape::Box::new(archetype.hitbox.0, 0., archetype.hitbox.1).into()),
+                archetype: archetype.clone(),
+            })
+            .collect();
+        log::info!("Loaded {} bug archetypes", registry.kinds.len());
+    }
+}
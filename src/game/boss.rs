@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+
+use crate::rng::GameRng;
+
+use super::{
+    archetype::{BossArchetype, BossPhase, BugRegistry},
+    components::*,
+    hit::BugHitbox,
+    systems::spawn_bug,
+    BugsSpawnTimer,
+};
+
+/// Seconds of the flash and the stop of every hit
+const FLASH_SECS: f32 = 0.15;
+/// Alpha of the hitbox at the start of the flash
+const FLASH_ALPHA: f32 = 0.6;
+/// Size of the health bar on the world
+const BAR_SIZE: Vec2 = Vec2::new(3., 0.3);
+
+//
+// Boss state, the phases are started by the health left
+//
+#[derive(Component)]
+pub(super) struct Boss {
+    phases: Vec<BossPhase>,
+    /// Phases already started
+    pub(super) phase: usize,
+    /// Clicks already flashed
+    clicks: u32,
+    flash: Timer,
+    /// Own material of the hitbox, to flash only this boss
+    material: Handle<StandardMaterial>,
+}
+
+impl Boss {
+    pub fn new(archetype: &BossArchetype) -> Self {
+        let mut flash = Timer::from_seconds(FLASH_SECS, TimerMode::Once);
+        flash.set_elapsed(flash.duration());
+        Self {
+            phases: archetype.phases.clone(),
+            phase: 0,
+            clicks: 0,
+            flash,
+            material: Handle::default(),
+        }
+    }
+
+    /// Phases started with the health left, as fraction of the max
+    fn advance(&mut self, health: f32) -> Vec<BossPhase> {
+        let mut started = Vec::new();
+        while let Some(phase) = self.phases.get(self.phase) {
+            if health > phase.health {
+                break;
+            }
+            started.push(phase.clone());
+            self.phase += 1;
+        }
+        started
+    }
+}
+
+#[derive(Resource)]
+pub(super) struct BossAssets {
+    pub(super) bar: Handle<Mesh>,
+    pub(super) background: Handle<StandardMaterial>,
+    pub(super) fill: Handle<StandardMaterial>,
+}
+
+impl BossAssets {
+    pub fn new(meshes: &mut Assets<Mesh>, materials: &mut Assets<StandardMaterial>) -> Self {
+        let unlit = |color: Color| StandardMaterial {
+            base_color: color,
+            unlit: true,
+            ..default()
+        };
+        Self {
+            bar: meshes.add(shape::Quad::new(BAR_SIZE).into()),
+            background: materials.add(unlit(Color::rgba(0.2, 0.2, 0.2, 0.8))),
+            fill: materials.add(unlit(Color::rgb(0.85, 0.15, 0.15))),
+        }
+    }
+}
+
+/// Follows the boss on the world, without turn with it
+#[derive(Component)]
+pub(super) struct HealthBar {
+    bug: Entity,
+    /// Distance over the boss
+    offset: f32,
+}
+
+#[derive(Component)]
+pub(super) struct HealthBarFill;
+
+pub(super) fn setup_bosses(
+    mut cmd: Commands,
+    assets: Res<BossAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    hitboxes: Query<(), With<BugHitbox>>,
+    mut bosses: Query<(Entity, &BugData, &mut Boss, &Children), Added<Boss>>,
+) {
+    for (entity, data, mut boss, children) in bosses.iter_mut() {
+        boss.material = materials.add(Color::WHITE.with_a(0.).into());
+        for child in children.iter().filter(|child| hitboxes.contains(**child)) {
+            cmd.entity(*child).insert(boss.material.clone());
+        }
+        cmd.spawn((
+            PbrBundle {
+                mesh: assets.bar.clone(),
+                material: assets.background.clone(),
+                ..default()
+            },
+            HealthBar {
+                bug: entity,
+                offset: data.hitbox.max_element() / 2. + 0.5,
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                PbrBundle {
+                    mesh: assets.bar.clone(),
+                    material: assets.fill.clone(),
+                    transform: Transform::from_xyz(0., 0., 0.01),
+                    ..default()
+                },
+                HealthBarFill,
+            ));
+        });
+    }
+}
+
+//
+// Hit flash and phases of the bosses
+//
+pub(super) fn boss_update(
+    mut cmd: Commands,
+    time: Res<Time>,
+    registry: Res<BugRegistry>,
+    spawn_data: Res<BugsSpawnTimer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rnd: ResMut<GameRng>,
+    mut bosses: Query<(&mut Boss, &mut BugData, &mut BugPathWalk, &Transform)>,
+) {
+    for (mut boss, mut data, mut path, transform) in bosses.iter_mut() {
+        if data.clicks > boss.clicks {
+            boss.clicks = data.clicks;
+            boss.flash.reset();
+            if !data.is_dead() {
+                data.state = BugState::Hurt;
+            }
+        }
+        if !boss.flash.tick(time.delta()).finished() || boss.flash.just_finished() {
+            if let Some(material) = materials.get_mut(&boss.material) {
+                let alpha = FLASH_ALPHA * boss.flash.percent_left();
+                material.base_color.set_a(alpha);
+            }
+        }
+        if data.is_dead() {
+            continue;
+        }
+        if data.state == BugState::Hurt && boss.flash.finished() {
+            data.state = if boss.phase > 0 {
+                BugState::Enraged
+            } else {
+                BugState::Walking
+            };
+        }
+
+        let pos = transform.translation;
+        for phase in boss.advance(data.health()) {
+            log::debug!("Boss {} phase: {phase:?}", data.archetype);
+            let speed = path.speed * phase.speed;
+            path.speed = speed;
+            if let Some(style) = phase.path {
+                let mut points = style.generate(&mut *rnd);
+                points[0] = pos;
+                *path = BugPathWalk::new(points, speed);
+            }
+            if let Some((bug, count)) = &phase.split {
+                let Some(kind) = registry.get(bug) else {
+                    log::warn!("Unknown bug archetype: {bug}");
+                    continue;
+                };
+                for _ in 0..*count {
+                    let mut points = kind.archetype.path.generate(&mut *rnd);
+                    points[0] = pos;
+                    spawn_bug(
+                        &mut cmd,
+                        &spawn_data.material,
+                        kind,
+                        points,
+                        kind.archetype.speed,
+                    );
+                }
+            }
+            if data.state != BugState::Hurt {
+                data.state = BugState::Enraged;
+            }
+        }
+    }
+}
+
+//
+// Place the health bars over the bosses, removed with them
+//
+pub(super) fn health_bars(
+    mut cmd: Commands,
+    bugs: Query<(&Transform, &BugData)>,
+    mut bars: Query<(Entity, &HealthBar, &mut Transform, &Children), Without<BugData>>,
+    mut fills: Query<&mut Transform, (With<HealthBarFill>, Without<HealthBar>, Without<BugData>)>,
+) {
+    for (entity, bar, mut transform, children) in bars.iter_mut() {
+        let Some((bug, data)) = bugs.get(bar.bug).ok().filter(|(_, data)| !data.is_dead()) else {
+            cmd.entity(entity).despawn_recursive();
+            continue;
+        };
+        transform.translation = bug.translation + Vec3::new(0., bar.offset, 2.);
+        let health = data.health();
+        for child in children.iter() {
+            let Ok(mut fill) = fills.get_mut(*child) else { continue; };
+            fill.scale.x = health;
+            fill.translation.x = -BAR_SIZE.x * (1. - health) / 2.;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::PathStyle;
+
+    use super::*;
+
+    fn boss() -> Boss {
+        Boss::new(&BossArchetype {
+            phases: vec![
+                BossPhase {
+                    health: 0.66,
+                    speed: 1.5,
+                    path: None,
+                    split: Some(("spider".to_string(), 3)),
+                },
+                BossPhase {
+                    health: 0.33,
+                    speed: 2.,
+                    path: Some(PathStyle::Spiral),
+                    split: None,
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn phases_start_once_by_health() {
+        let mut boss = boss();
+        assert!(boss.advance(1.).is_empty());
+        assert!(boss.advance(0.7).is_empty());
+        let started = boss.advance(0.6);
+        assert_eq!(started.len(), 1);
+        assert_eq!(started[0].speed, 1.5);
+        assert!(boss.advance(0.5).is_empty());
+        assert_eq!(boss.phase, 1);
+    }
+
+    #[test]
+    fn big_hits_start_several_phases() {
+        let mut boss = boss();
+        assert_eq!(boss.advance(0.).len(), 2);
+        assert!(boss.advance(0.).is_empty());
+        assert_eq!(boss.phase, 2);
+    }
+}
@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_tweening::{Animator, EaseFunction, Tween};
+
+use crate::{
+    lens::ThemeAlphaLens,
+    settings::Settings,
+    ui::{Palette, ThemeColor, ThemeRole},
+};
+
+/// Seconds after a kill to keep the streak alive
+const COMBO_WINDOW: f32 = 1.5;
+/// Kills needed to raise the multiplier one level
+const KILLS_PER_LEVEL: u32 = 3;
+const MAX_LEVEL: u32 = 4;
+
+//
+// Kills close in time build a streak, each level of the
+// streak adds one to the score multiplier
+//
+#[derive(Resource, Debug, Clone)]
+pub(crate) struct Combo {
+    pub streak: u32,
+    pub best_streak: u32,
+    timer: Timer,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(COMBO_WINDOW, TimerMode::Once);
+        timer.tick(timer.duration());
+        Self {
+            streak: 0,
+            best_streak: 0,
+            timer,
+        }
+    }
+}
+
+impl Combo {
+    pub fn level(&self) -> u32 {
+        (self.streak.saturating_sub(1) / KILLS_PER_LEVEL).min(MAX_LEVEL)
+    }
+
+    pub fn multiplier(&self) -> u64 {
+        1 + self.level() as u64
+    }
+
+    /// Add a kill to the streak, returns the points earned with the multiplier
+    pub fn kill(&mut self, score: u64) -> u64 {
+        self.streak = if self.timer.finished() {
+            1
+        } else {
+            self.streak + 1
+        };
+        self.best_streak = self.best_streak.max(self.streak);
+        self.timer.reset();
+        score * self.multiplier()
+    }
+
+    pub fn tick(&mut self, delta: Duration) {
+        if self.timer.tick(delta).just_finished() {
+            self.streak = 0;
+        }
+    }
+
+    pub fn break_streak(&mut self) {
+        self.streak = 0;
+        let duration = self.timer.duration();
+        self.timer.tick(duration);
+    }
+}
+
+//
+// Points earned by a kill, shown floating on the kill position
+// when the kill is part of a streak
+//
+#[derive(Event)]
+pub(crate) struct ComboEvent {
+    pub points: u64,
+    pub streak: u32,
+    /// The kill raised the multiplier
+    pub level_up: bool,
+    pub pos: Vec3,
+}
+
+#[derive(Component)]
+pub(super) struct FloatingText {
+    timer: Timer,
+}
+
+pub(super) fn combo_update(time: Res<Time>, mut combo: ResMut<Combo>) {
+    combo.tick(time.delta());
+}
+
+pub(super) fn spawn_combo_text(
+    mut cmd: Commands,
+    asset_server: Res<AssetServer>,
+    camera: Query<(&Camera, &GlobalTransform)>,
+    mut events: EventReader<ComboEvent>,
+) {
+    let camera = camera.get_single().ok();
+    for event in events.iter() {
+        if event.streak < 2 {
+            continue;
+        }
+        let Some(pos) = camera
+            .and_then(|(camera, transform)| camera.world_to_viewport(transform, event.pos))
+            else { continue; };
+        cmd.spawn((
+            TextBundle {
+                text: Text::from_section(
+                    format!("+{}", event.points),
+                    TextStyle {
+                        font: asset_server.load("fonts/Lato-Regular.ttf"),
+                        font_size: 32. + 8. * event.streak.min(8) as f32,
+                        color: Palette::DEFAULT.accent,
+                    },
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(pos.x),
+                    top: Val::Px(pos.y),
+                    ..default()
+                },
+                ..default()
+            },
+            FloatingText {
+                timer: Timer::from_seconds(1., TimerMode::Once),
+            },
+            ThemeColor::new(ThemeRole::Accent),
+            Animator::new(Tween::new(
+                EaseFunction::QuadraticIn,
+                Duration::from_secs(1),
+                ThemeAlphaLens::create(1., 0.),
+            )),
+        ));
+    }
+}
+
+pub(super) fn float_combo_text(
+    mut cmd: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut texts: Query<(Entity, &mut Style, &mut FloatingText)>,
+) {
+    for (entity, mut style, mut text) in texts.iter_mut() {
+        if text.timer.tick(time.delta()).finished() {
+            cmd.entity(entity).despawn_recursive();
+            continue;
+        }
+        if settings.reduced_motion {
+            continue;
+        }
+        if let Val::Px(top) = style.top {
+            style.top = Val::Px(top - 60. * time.delta_seconds());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kills_in_window_raise_multiplier() {
+        let mut combo = Combo::default();
+        let points = (0..KILLS_PER_LEVEL + 1)
+            .map(|_| combo.kill(1))
+            .collect::<Vec<_>>();
+        assert_eq!(points, vec![1, 1, 1, 2]);
+        assert_eq!(combo.streak, KILLS_PER_LEVEL + 1);
+        assert_eq!(combo.multiplier(), 2);
+    }
+
+    #[test]
+    fn multiplier_is_capped() {
+        let mut combo = Combo::default();
+        for _ in 0..100 {
+            combo.kill(1);
+        }
+        assert_eq!(combo.multiplier(), 1 + MAX_LEVEL as u64);
+        assert_eq!(combo.best_streak, 100);
+    }
+
+    #[test]
+    fn streak_ends_after_window() {
+        let mut combo = Combo::default();
+        combo.kill(1);
+        combo.kill(1);
+        combo.tick(Duration::from_secs_f32(COMBO_WINDOW + 0.1));
+        assert_eq!(combo.streak, 0);
+        combo.kill(1);
+        assert_eq!(combo.streak, 1);
+    }
+
+    #[test]
+    fn miss_breaks_streak() {
+        let mut combo = Combo::default();
+        for _ in 0..KILLS_PER_LEVEL * 2 {
+            combo.kill(1);
+        }
+        combo.break_streak();
+        assert_eq!(combo.streak, 0);
+        assert_eq!(combo.kill(5), 5);
+        assert_eq!(combo.best_streak, KILLS_PER_LEVEL * 2);
+    }
+}
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 
-use super::{BugAnimations, CRAB_SCORES};
+use crate::path::ArcLengthPath;
+
+use super::{BugAnimations, BugArchetype, BugSounds};
 
 //
 // Score Text Identifier
@@ -8,23 +10,43 @@ use super::{BugAnimations, CRAB_SCORES};
 #[derive(Component)]
 pub struct ScoreText;
 
+//
+// Lives or Time Text Identifier
+//
+#[derive(Component)]
+pub struct RoundText;
+
 //
 // Patrol Data for bugs
 //
 #[derive(Component, Clone, Reflect)]
 #[reflect]
 pub(super) struct BugPathWalk {
-    pub(super) current_path: usize,
-    pub(super) points: Vec<Vec3>,
+    pub(super) path: ArcLengthPath,
+    pub(super) distance: f32, // walked from the start of path
     pub(super) speed: f32,
 }
 
+impl BugPathWalk {
+    pub fn new(points: Vec<Vec3>, speed: f32) -> Self {
+        Self {
+            path: ArcLengthPath::new(points),
+            distance: 0.,
+            speed,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default, Reflect, PartialEq, Eq)]
 #[reflect]
 pub(super) enum BugState {
     #[default]
     Idle,
     Walking,
+    /// Stopped for a moment by a hit, only the bosses
+    Hurt,
+    /// Walking after the first phase of a boss
+    Enraged,
     Death,
 }
 
@@ -34,13 +56,21 @@ pub(super) enum BugState {
 #[derive(Component, Default, Reflect)]
 #[reflect]
 pub(super) struct BugData {
-    pub(super) clicks: u8,
-    pub(super) max_clicks: u8,
+    pub(super) archetype: String,
+    pub(super) hit_effect: Option<String>,
+    pub(super) death_effect: Option<String>,
+    pub(super) clicks: u32,
+    pub(super) max_clicks: u32,
+    pub(super) score: u64,
+    /// Width and length of the area hit by the actions
+    pub(super) hitbox: Vec2,
     pub(super) wait_for_remove: Timer, // when is dead, this tick for despawn entity
     pub(super) last_state: BugState,
     pub(super) state: BugState,
     #[reflect(ignore)]
     pub(super) animations: BugAnimations,
+    #[reflect(ignore)]
+    pub(super) sounds: BugSounds,
 }
 
 impl BugData {
@@ -48,12 +78,22 @@ impl BugData {
         self.clicks >= self.max_clicks
     }
 
-    pub fn factory(score: u64, animations: BugAnimations) -> Self {
-        let max_clicks = if CRAB_SCORES.contains(&score) { 2 } else { 1 };
+    /// Fraction of the clicks left to kill it
+    pub fn health(&self) -> f32 {
+        (1. - self.clicks as f32 / self.max_clicks.max(1) as f32).max(0.)
+    }
+
+    pub fn factory(archetype: &BugArchetype, animations: BugAnimations, sounds: BugSounds) -> Self {
         Self {
+            archetype: archetype.name.clone(),
+            hit_effect: archetype.hit_effect.clone(),
+            death_effect: archetype.death_effect.clone(),
             clicks: 0,
-            max_clicks,
+            max_clicks: archetype.max_clicks,
+            score: archetype.score,
+            hitbox: Vec2::new(archetype.hitbox.0, archetype.hitbox.1),
             animations,
+            sounds,
             state: BugState::Idle,
             last_state: BugState::Idle,
             wait_for_remove: Timer::from_seconds(3., TimerMode::Once),
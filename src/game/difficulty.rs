@@ -0,0 +1,286 @@
+use std::ops::RangeInclusive;
+
+use bevy::{prelude::*, reflect::TypeUuid, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{
+    config::{Difficulty, GameConfig},
+    data::RonAsset,
+};
+
+use super::rules::SpawnContext;
+
+//
+// Value which moves with the score or the seconds played
+//
+#[derive(Clone, Copy, Debug, Default, Deserialize, Reflect, PartialEq)]
+pub enum CurveInput {
+    #[default]
+    Score,
+    /// Seconds since the game started
+    Elapsed,
+}
+
+/// Points `(input, value)` sorted by the input, linear between them
+/// and flat before the first and after the last one
+#[derive(Clone, Debug, Deserialize, Reflect, PartialEq)]
+pub struct Curve {
+    #[serde(default)]
+    pub by: CurveInput,
+    pub points: Vec<(f32, f32)>,
+}
+
+impl Curve {
+    pub fn new(by: CurveInput, points: &[(f32, f32)]) -> Self {
+        Self {
+            by,
+            points: points.to_vec(),
+        }
+    }
+
+    pub fn constant(value: f32) -> Self {
+        Self::new(CurveInput::Score, &[(0., value)])
+    }
+
+    pub fn sample(&self, ctx: &SpawnContext) -> f32 {
+        let x = match self.by {
+            CurveInput::Score => ctx.score as f32,
+            CurveInput::Elapsed => ctx.elapsed,
+        };
+        self.at(x)
+    }
+
+    fn at(&self, x: f32) -> f32 {
+        let Some(&(first_x, first)) = self.points.first() else { return 0.; };
+        if x <= first_x {
+            return first;
+        }
+        for pair in self.points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            if x <= x1 {
+                if x1 - x0 <= f32::EPSILON {
+                    return y1;
+                }
+                return y0 + (y1 - y0) * (x - x0) / (x1 - x0);
+            }
+        }
+        self.points[self.points.len() - 1].1
+    }
+}
+
+//
+// How the game gets harder, the presets are on `Difficulty`
+// and custom ones are loaded from `*.difficulty.ron` assets
+//
+#[derive(Resource, Clone, Debug, Deserialize, Reflect, TypeUuid)]
+#[uuid = "0d7c4b2e-6a1f-4f3d-8e95-2b7a9c1e5f48"]
+pub struct DifficultyCurve {
+    /// Seconds between spawns, random between both curves
+    pub spawn_interval: (Curve, Curve),
+    /// Multiplier of the speed of the bugs
+    pub speed: Curve,
+    /// Max bugs alive, as fraction from 0 to 1 of `GameConfig::max_bugs_on_screen`
+    pub max_bugs: Curve,
+    /// Multiplier of the weight of the spawn rules by bug archetype
+    #[serde(default)]
+    pub weights: HashMap<String, Curve>,
+}
+
+impl RonAsset for DifficultyCurve {
+    const EXTENSIONS: &'static [&'static str] = &["difficulty.ron"];
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        Self::normal()
+    }
+}
+
+impl DifficultyCurve {
+    pub fn easy() -> Self {
+        Self {
+            spawn_interval: (
+                Curve::new(CurveInput::Score, &[(0., 3.), (300., 2.)]),
+                Curve::new(CurveInput::Score, &[(0., 6.), (300., 4.)]),
+            ),
+            speed: Curve::new(CurveInput::Score, &[(0., 0.8), (300., 1.1)]),
+            max_bugs: Curve::new(CurveInput::Score, &[(0., 0.5), (200., 1.)]),
+            weights: HashMap::default(),
+        }
+    }
+
+    pub fn normal() -> Self {
+        Self {
+            spawn_interval: (
+                Curve::new(CurveInput::Score, &[(0., 2.), (200., 1.)]),
+                Curve::new(CurveInput::Score, &[(0., 5.), (200., 2.5)]),
+            ),
+            speed: Curve::new(CurveInput::Score, &[(0., 1.), (300., 1.5)]),
+            max_bugs: Curve::constant(1.),
+            weights: HashMap::default(),
+        }
+    }
+
+    pub fn hard() -> Self {
+        Self {
+            spawn_interval: (
+                Curve::new(CurveInput::Score, &[(0., 1.2), (150., 0.5)]),
+                Curve::new(CurveInput::Score, &[(0., 3.), (150., 1.2)]),
+            ),
+            speed: Curve::new(CurveInput::Score, &[(0., 1.2), (200., 2.)]),
+            max_bugs: Curve::constant(1.),
+            // more bugs which need two hits
+            weights: HashMap::from_iter([(
+                "crab".to_string(),
+                Curve::new(CurveInput::Score, &[(0., 1.), (100., 3.)]),
+            )]),
+        }
+    }
+
+    /// The curves of a preset, `None` for the custom ones
+    pub fn preset(difficulty: &Difficulty) -> Option<Self> {
+        match difficulty {
+            Difficulty::Easy => Some(Self::easy()),
+            Difficulty::Normal => Some(Self::normal()),
+            Difficulty::Hard => Some(Self::hard()),
+            Difficulty::Custom(_) => None,
+        }
+    }
+
+    /// Range of seconds to wait for the next bug
+    pub fn spawn_interval(&self, ctx: &SpawnContext) -> RangeInclusive<f32> {
+        let a = self.spawn_interval.0.sample(ctx).max(0.1);
+        let b = self.spawn_interval.1.sample(ctx).max(0.1);
+        a.min(b)..=a.max(b)
+    }
+
+    pub fn speed(&self, ctx: &SpawnContext) -> f32 {
+        self.speed.sample(ctx).max(0.)
+    }
+
+    /// Part of the `max` bugs alive, at least one
+    pub fn max_bugs(&self, ctx: &SpawnContext, max: usize) -> usize {
+        let factor = self.max_bugs.sample(ctx).clamp(0., 1.);
+        ((max as f32 * factor).round() as usize).clamp(1, max.max(1))
+    }
+
+    pub fn weight(&self, bug: &str, ctx: &SpawnContext) -> f32 {
+        self.weights
+            .get(bug)
+            .map_or(1., |curve| curve.sample(ctx).max(0.))
+    }
+}
+
+//
+// Follow the difficulty of the config, also when it's changed at runtime
+//
+pub(super) fn load_difficulty(
+    config: Res<GameConfig>,
+    asset_server: Res<AssetServer>,
+    curves: Res<Assets<DifficultyCurve>>,
+    mut events: EventReader<AssetEvent<DifficultyCurve>>,
+    mut source: Local<Handle<DifficultyCurve>>,
+    mut difficulty: ResMut<DifficultyCurve>,
+) {
+    if config.is_changed() {
+        match &config.difficulty {
+            Difficulty::Custom(path) => {
+                *source = asset_server.load(path.as_str());
+                // already loaded by a previous config
+                if let Some(curve) = curves.get(&source) {
+                    *difficulty = curve.clone();
+                }
+            }
+            preset => {
+                *source = Handle::default();
+                *difficulty = DifficultyCurve::preset(preset).unwrap_or_default();
+            }
+        }
+    }
+    for event in events.iter() {
+        let (AssetEvent::Created { handle } | AssetEvent::Modified { handle }) = event else {
+            continue;
+        };
+        if *handle != *source {
+            continue;
+        }
+        let Some(curve) = curves.get(handle) else { continue; };
+        *difficulty = curve.clone();
+        log::info!("Loaded difficulty curve: {curve:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(score: u64, elapsed: f32) -> SpawnContext {
+        SpawnContext { score, elapsed }
+    }
+
+    #[test]
+    fn linear_between_points() {
+        let curve = Curve::new(CurveInput::Score, &[(10., 1.), (20., 3.), (40., 2.)]);
+        assert_eq!(curve.sample(&ctx(0, 0.)), 1.);
+        assert_eq!(curve.sample(&ctx(10, 0.)), 1.);
+        assert_eq!(curve.sample(&ctx(15, 0.)), 2.);
+        assert_eq!(curve.sample(&ctx(30, 0.)), 2.5);
+        assert_eq!(curve.sample(&ctx(100, 0.)), 2.);
+        assert_eq!(Curve::constant(4.).sample(&ctx(100, 100.)), 4.);
+    }
+
+    #[test]
+    fn default_allows_every_bug_on_screen() {
+        let curve = DifficultyCurve::default();
+        assert_eq!(curve.max_bugs(&ctx(0, 0.), 30), 30);
+        assert_eq!(curve.max_bugs(&ctx(0, 0.), 50), 50);
+        assert_eq!(DifficultyCurve::easy().max_bugs(&ctx(0, 0.), 30), 15);
+        assert_eq!(DifficultyCurve::easy().max_bugs(&ctx(0, 0.), 1), 1);
+    }
+
+    #[test]
+    fn curve_by_elapsed_time() {
+        let curve = Curve::new(CurveInput::Elapsed, &[(0., 1.), (60., 2.)]);
+        assert_eq!(curve.sample(&ctx(500, 30.)), 1.5);
+    }
+
+    #[test]
+    fn presets_get_harder() {
+        let start = ctx(0, 0.);
+        let late = ctx(1000, 600.);
+        let presets = [
+            DifficultyCurve::easy(),
+            DifficultyCurve::normal(),
+            DifficultyCurve::hard(),
+        ];
+        for curve in &presets {
+            assert!(curve.speed(&late) > curve.speed(&start));
+            assert!(curve.max_bugs(&late, 30) >= curve.max_bugs(&start, 30));
+            assert_eq!(curve.max_bugs(&late, 30), 30);
+            assert!(curve.spawn_interval(&late).end() < curve.spawn_interval(&start).end());
+        }
+        for pair in presets.windows(2) {
+            assert!(pair[1].speed(&start) >= pair[0].speed(&start));
+            assert!(pair[1].max_bugs(&start, 30) >= pair[0].max_bugs(&start, 30));
+            assert!(
+                pair[1].spawn_interval(&start).start() <= pair[0].spawn_interval(&start).start()
+            );
+        }
+        assert_eq!(presets[0].weight("crab", &late), 1.);
+        assert_eq!(presets[2].weight("crab", &late), 3.);
+    }
+
+    #[test]
+    fn custom_curve_from_ron() {
+        let curve: DifficultyCurve = ron::from_str(include_str!(
+            "../../assets/difficulty/survival.difficulty.ron"
+        ))
+        .unwrap();
+        assert_eq!(curve.spawn_interval(&ctx(500, 0.)), 2.0..=4.0);
+        assert!((curve.speed(&ctx(0, 30.)) - 1.1).abs() < 1e-5);
+        assert_eq!(curve.max_bugs(&ctx(0, 0.), 30), 8);
+        assert_eq!(curve.max_bugs(&ctx(0, 180.), 30), 30);
+        assert_eq!(curve.weight("crab", &ctx(0, 0.)), 0.5);
+        assert_eq!(curve.weight("spider", &ctx(0, 0.)), 1.);
+    }
+}
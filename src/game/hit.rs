@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+use crate::{
+    input::{ActionEvent, GameAction},
+    settings::Settings,
+};
+
+use super::{components::*, BugEntityClickedEvent, MissEvent};
+
+/// Invisible mesh of a bug picked by the hits, apart of the bug
+/// to follow the size of the hitboxes setting
+#[derive(Component)]
+pub(super) struct BugHitbox;
+
+//
+// Hit actions picked on the hitbox of a bug or on the background
+//
+pub(super) fn hit_detect(
+    hitboxes: Query<&Parent, With<BugHitbox>>,
+    bugs: Query<&BugData>,
+    mut actions: EventReader<ActionEvent>,
+    mut clicked: EventWriter<BugEntityClickedEvent>,
+    mut misses: EventWriter<MissEvent>,
+) {
+    let hits = actions
+        .iter()
+        .filter(|event| event.action == GameAction::Hit)
+        .filter_map(|event| event.target);
+    for (target, pos) in hits {
+        let Ok(bug) = hitboxes.get(target).map(Parent::get) else {
+            misses.send(MissEvent {
+                pos: pos.unwrap_or_default(),
+            });
+            continue;
+        };
+        if bugs.get(bug).map_or(false, |data| !data.is_dead()) {
+            clicked.send(BugEntityClickedEvent(bug, pos));
+        }
+    }
+}
+
+//
+// Size of the hitboxes of the setting, the dead bugs don't
+// take the hits of the bugs behind them
+//
+pub(super) fn update_hitboxes(
+    mut cmd: Commands,
+    settings: Res<Settings>,
+    bugs: Query<&BugData>,
+    mut hitboxes: Query<(Entity, &Parent, &mut Transform, Ref<BugHitbox>), With<RaycastPickTarget>>,
+) {
+    for (entity, bug, mut transform, hitbox) in hitboxes.iter_mut() {
+        if settings.is_changed() || hitbox.is_added() {
+            transform.scale = Vec3::splat(settings.hitbox_scale);
+        }
+        if bugs.get(bug.get()).map_or(false, BugData::is_dead) {
+            cmd.entity(entity).remove::<RaycastPickTarget>();
+        }
+    }
+}
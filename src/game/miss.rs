@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::*;
+
+use crate::{audio::SfxEvent, effects::EffectTypeEvent};
+
+use super::combo::Combo;
+
+//
+// Hit action which reaches no bug, with the world position on the background
+//
+#[derive(Event)]
+pub(crate) struct MissEvent {
+    pub pos: Vec3,
+}
+
+#[derive(Resource)]
+pub(super) struct MissSound(Handle<AudioSource>);
+
+pub(super) fn load_miss_sound(mut cmd: Commands, asset_server: Res<AssetServer>) {
+    cmd.insert_resource(MissSound(asset_server.load("audio/miss.wav")));
+}
+
+//
+// Invisible plane behind the bugs, the bug hitboxes block the
+// picking so only the hits on empty space reach it
+//
+pub(super) fn spawn_miss_target(
+    mut cmd: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    cmd.spawn((
+        PbrBundle {
+            mesh: meshes.add(shape::Quad::new(Vec2::new(100., 60.)).into()),
+            material: materials.add(StandardMaterial {
+                base_color: Color::NONE,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
+            transform: Transform::from_xyz(0., 0., -2.),
+            ..default()
+        },
+        PickableBundle::default(),
+        RaycastPickTarget::default(),
+    ));
+}
+
+pub(super) fn miss_detect(
+    sound: Res<MissSound>,
+    mut combo: ResMut<Combo>,
+    mut misses: EventReader<MissEvent>,
+    mut effect: EventWriter<EffectTypeEvent>,
+    mut sfx: EventWriter<SfxEvent>,
+) {
+    for miss in misses.iter() {
+        combo.break_streak();
+        sfx.send(SfxEvent::new(sound.0.clone()).with_volume(0.5).at(miss.pos));
+        effect.send(EffectTypeEvent::Miss { pos: miss.pos });
+    }
+}
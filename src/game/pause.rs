@@ -0,0 +1,78 @@
+use bevy::{prelude::*, window::WindowFocused};
+
+use crate::{
+    input::{ActionEvent, GameAction},
+    GameState,
+};
+
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PauseEvent {
+    Pause,
+    Resume,
+    Toggle,
+}
+
+//
+// Pause when the window lost the focus
+//
+// Bevy 0.11 has no event for the suspend of the app, but winit sends the
+// focus lost before it, when an Android app goes to the background and
+// when the canvas of the web loses the focus. A hidden browser tab stops
+// the frames, so the game is frozen until it is visible again.
+//
+pub(super) fn focus_pause(
+    mut focused: EventReader<WindowFocused>,
+    mut pause: EventWriter<PauseEvent>,
+) {
+    if focused.iter().any(|event| !event.focused) {
+        pause.send(PauseEvent::Pause);
+    }
+}
+
+pub(super) fn action_pause(
+    mut actions: EventReader<ActionEvent>,
+    mut pause: EventWriter<PauseEvent>,
+) {
+    if actions
+        .iter()
+        .any(|event| event.action == GameAction::Pause)
+    {
+        pause.send(PauseEvent::Toggle);
+    }
+}
+
+pub(super) fn pause_game(
+    mut events: EventReader<PauseEvent>,
+    state: Res<State<GameState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let mut paused = *state.get() == GameState::Paused;
+    let playing = paused || *state.get() == GameState::Game;
+    for event in events.iter() {
+        paused = match event {
+            PauseEvent::Pause => true,
+            PauseEvent::Resume => false,
+            PauseEvent::Toggle => !paused,
+        };
+    }
+    if !playing || paused == (*state.get() == GameState::Paused) {
+        return;
+    }
+    game_state.set(if paused {
+        GameState::Paused
+    } else {
+        GameState::Game
+    });
+}
+
+//
+// Freeze the game clock, so the movement, spawn timers, animations,
+// tweens and effects stop until the game is resumed
+//
+pub(super) fn freeze_time(mut time: ResMut<Time>) {
+    time.pause();
+}
+
+pub(super) fn unfreeze_time(mut time: ResMut<Time>) {
+    time.unpause();
+}
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    config::{GameConfig, GameMode},
+    rng::GameRng,
+    GameState,
+};
+
+use super::{components::*, waves::Waves, BugEscapedEvent, BugsSpawnTimer, ScoreTextResource};
+
+//
+// Current round, ends when the lives or the time are over
+//
+#[derive(Resource, Debug, Clone)]
+pub(crate) struct Round {
+    pub lives: Option<u32>,
+    pub time: Option<Timer>,
+    pub escaped: u64,
+    /// Every wave of the level is cleared
+    pub cleared: bool,
+}
+
+impl Round {
+    pub fn new(mode: GameMode) -> Self {
+        let (lives, time) = match mode {
+            GameMode::Endless => (None, None),
+            GameMode::Lives(lives) => (Some(lives), None),
+            GameMode::Timed(secs) => (None, Some(Timer::from_seconds(secs, TimerMode::Once))),
+        };
+        Self {
+            lives,
+            time,
+            escaped: 0,
+            cleared: false,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.cleared || self.lives == Some(0) || self.time.as_ref().map_or(false, Timer::finished)
+    }
+}
+
+#[derive(Event)]
+pub(crate) struct RestartEvent;
+
+pub(super) fn round_update(
+    time: Res<Time>,
+    mut round: ResMut<Round>,
+    mut escaped: EventReader<BugEscapedEvent>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for _ in escaped.iter() {
+        round.escaped += 1;
+        if let Some(lives) = round.lives.as_mut() {
+            *lives = lives.saturating_sub(1);
+        }
+    }
+    if let Some(timer) = round.time.as_mut() {
+        timer.tick(time.delta());
+    }
+    if round.is_over() {
+        game_state.set(GameState::GameOver);
+    }
+}
+
+pub(super) fn restart_round(
+    mut cmd: Commands,
+    config: Res<GameConfig>,
+    bugs: Query<Entity, With<BugData>>,
+    mut round: ResMut<Round>,
+    mut score: ResMut<ScoreTextResource>,
+    mut combo: ResMut<Combo>,
+    mut spawn_data: ResMut<BugsSpawnTimer>,
+    mut waves: ResMut<Waves>,
+    mut rnd: ResMut<GameRng>,
+    mut restart: EventReader<RestartEvent>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if restart.iter().count() == 0 {
+        return;
+    }
+    for bug in bugs.iter() {
+        cmd.entity(bug).despawn_recursive();
+    }
+    *round = Round::new(config.mode);
+    score.0 = 0;
+    *combo = Combo::default();
+    spawn_data.elapsed = 0.;
+    spawn_data.timer.set_duration(Duration::from_secs(2));
+    spawn_data.timer.reset();
+    waves.restart();
+    // same seed, same bugs, without seed every round is different
+    if config.seed.is_some() {
+        rnd.reset();
+    } else {
+        *rnd = GameRng::random();
+    }
+    game_state.set(GameState::Game);
+}
+
+//
+// Print on screen the lives or the remaining time
+//
+pub(super) fn round_print(mut text: Query<&mut Text, With<RoundText>>, round: Res<Round>) {
+    let Ok(mut text) = text.get_single_mut() else { return; };
+    let value = match (&round.lives, &round.time) {
+        (Some(lives), _) => lives.to_string(),
+        (_, Some(timer)) => format!("{:.0}", timer.remaining_secs().ceil()),
+        _ => return,
+    };
+    if let Some(section) = text.sections.last_mut() {
+        section.value = value;
+    }
+}
@@ -0,0 +1,290 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::Deserialize;
+
+use crate::{data::RonAsset, path::PathStyle};
+
+//
+// Conditions to spawn a bug
+//
+#[derive(Clone, Debug, Deserialize, Reflect, PartialEq)]
+pub enum SpawnCondition {
+    Always,
+    /// Exact scores
+    Scores(Vec<u64>),
+    /// Inclusive range of scores, without `max` has no limit
+    ScoreRange {
+        min: u64,
+        #[serde(default)]
+        max: Option<u64>,
+    },
+    /// `score % every == offset`
+    Modulo {
+        every: u64,
+        #[serde(default)]
+        offset: u64,
+    },
+    /// Seconds since the game started, without `max` has no limit
+    Elapsed {
+        min: f32,
+        #[serde(default)]
+        max: Option<f32>,
+    },
+    /// All the conditions must match
+    All(Vec<SpawnCondition>),
+}
+
+impl SpawnCondition {
+    pub fn matches(&self, ctx: &SpawnContext) -> bool {
+        match self {
+            SpawnCondition::Always => true,
+            SpawnCondition::Scores(scores) => scores.contains(&ctx.score),
+            SpawnCondition::ScoreRange { min, max } => {
+                ctx.score >= *min && max.map_or(true, |max| ctx.score <= max)
+            }
+            SpawnCondition::Modulo { every, offset } => *every != 0 && ctx.score % every == *offset,
+            SpawnCondition::Elapsed { min, max } => {
+                ctx.elapsed >= *min && max.map_or(true, |max| ctx.elapsed <= max)
+            }
+            SpawnCondition::All(conditions) => conditions.iter().all(|c| c.matches(ctx)),
+        }
+    }
+}
+
+//
+// Rule to choose which bug appears
+//
+#[derive(Clone, Debug, Deserialize, Reflect)]
+pub struct SpawnRule {
+    pub when: SpawnCondition,
+    /// Name of the bug archetype
+    pub bug: String,
+    /// Only the matched rules with the highest priority are candidates
+    #[serde(default)]
+    pub priority: i32,
+    /// Chance to be selected between the candidates
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    /// Override the speed of the archetype
+    #[serde(default)]
+    pub speed: Option<f32>,
+    #[serde(default)]
+    pub path: Option<PathStyle>,
+}
+
+fn default_weight() -> f32 {
+    1.
+}
+
+#[derive(Debug, Deserialize, Reflect, TypeUuid)]
+#[uuid = "5e2a9f43-1c8d-4b7a-b0e6-93d2f1a8c4e7"]
+pub struct SpawnRules {
+    pub rules: Vec<SpawnRule>,
+}
+
+impl RonAsset for SpawnRules {
+    const EXTENSIONS: &'static [&'static str] = &["rules.ron"];
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpawnContext {
+    pub score: u64,
+    /// Seconds since the game started
+    pub elapsed: f32,
+}
+
+impl SpawnRules {
+    pub fn choose(&self, ctx: &SpawnContext, rnd: &mut impl Rng) -> Option<&SpawnRule> {
+        self.choose_weighted(ctx, rnd, |_| 1.)
+    }
+
+    /// Like `choose`, with the weight of every candidate multiplied by `factor`
+    pub fn choose_weighted(
+        &self,
+        ctx: &SpawnContext,
+        rnd: &mut impl Rng,
+        factor: impl Fn(&SpawnRule) -> f32,
+    ) -> Option<&SpawnRule> {
+        let matched = self
+            .rules
+            .iter()
+            .filter(|r| r.when.matches(ctx))
+            .collect::<Vec<&SpawnRule>>();
+        let priority = matched.iter().map(|r| r.priority).max()?;
+        let candidates = matched
+            .into_iter()
+            .filter(|r| r.priority == priority)
+            .collect::<Vec<&SpawnRule>>();
+        let weights = WeightedIndex::new(candidates.iter().map(|r| r.weight * factor(r))).ok()?;
+        Some(candidates[weights.sample(rnd)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn rules(data: &str) -> SpawnRules {
+        ron::from_str(data).unwrap()
+    }
+
+    fn choose(rules: &SpawnRules, score: u64, elapsed: f32) -> Option<String> {
+        let mut rnd = StdRng::seed_from_u64(404);
+        rules
+            .choose(&SpawnContext { score, elapsed }, &mut rnd)
+            .map(|r| r.bug.clone())
+    }
+
+    #[test]
+    fn exact_scores_win_by_priority() {
+        let rules = rules(
+            r#"(rules: [
+                (when: Always, bug: "spider"),
+                (when: Scores([4, 40]), bug: "crab", priority: 1),
+            ])"#,
+        );
+        assert_eq!(choose(&rules, 3, 0.0).as_deref(), Some("spider"));
+        assert_eq!(choose(&rules, 4, 0.0).as_deref(), Some("crab"));
+        assert_eq!(choose(&rules, 40, 0.0).as_deref(), Some("crab"));
+    }
+
+    #[test]
+    fn score_range_is_inclusive() {
+        let rules = rules(r#"(rules: [(when: ScoreRange(min: 10, max: Some(20)), bug: "crab")])"#);
+        assert_eq!(choose(&rules, 9, 0.0), None);
+        assert_eq!(choose(&rules, 10, 0.0).as_deref(), Some("crab"));
+        assert_eq!(choose(&rules, 20, 0.0).as_deref(), Some("crab"));
+        assert_eq!(choose(&rules, 21, 0.0), None);
+    }
+
+    #[test]
+    fn open_score_range() {
+        let rules = rules(r#"(rules: [(when: ScoreRange(min: 100), bug: "crab")])"#);
+        assert_eq!(choose(&rules, 99, 0.0), None);
+        assert_eq!(choose(&rules, u64::MAX, 0.0).as_deref(), Some("crab"));
+    }
+
+    #[test]
+    fn modulo_pattern() {
+        let rules = rules(r#"(rules: [(when: Modulo(every: 5, offset: 2), bug: "crab")])"#);
+        assert_eq!(choose(&rules, 2, 0.0).as_deref(), Some("crab"));
+        assert_eq!(choose(&rules, 12, 0.0).as_deref(), Some("crab"));
+        assert_eq!(choose(&rules, 10, 0.0), None);
+
+        let zero = rules_with(SpawnCondition::Modulo {
+            every: 0,
+            offset: 0,
+        });
+        assert_eq!(choose(&zero, 0, 0.0), None);
+    }
+
+    #[test]
+    fn elapsed_time() {
+        let rules = rules(r#"(rules: [(when: Elapsed(min: 30.0, max: Some(60.0)), bug: "crab")])"#);
+        assert_eq!(choose(&rules, 0, 29.9), None);
+        assert_eq!(choose(&rules, 0, 45.0).as_deref(), Some("crab"));
+        assert_eq!(choose(&rules, 0, 60.1), None);
+    }
+
+    #[test]
+    fn all_conditions() {
+        let rules = rules(
+            r#"(rules: [(
+                when: All([ScoreRange(min: 10), Elapsed(min: 5.0)]),
+                bug: "crab",
+            )])"#,
+        );
+        assert_eq!(choose(&rules, 10, 1.0), None);
+        assert_eq!(choose(&rules, 1, 10.0), None);
+        assert_eq!(choose(&rules, 10, 10.0).as_deref(), Some("crab"));
+    }
+
+    #[test]
+    fn random_weights() {
+        let rules = rules(
+            r#"(rules: [
+                (when: Always, bug: "spider", weight: 3.0),
+                (when: Always, bug: "crab", weight: 1.0),
+                (when: Always, bug: "never", weight: 0.0),
+            ])"#,
+        );
+        let ctx = SpawnContext::default();
+        let mut rnd = StdRng::seed_from_u64(404);
+        let mut spiders = 0;
+        let mut crabs = 0;
+        for _ in 0..1000 {
+            match rules.choose(&ctx, &mut rnd).unwrap().bug.as_str() {
+                "spider" => spiders += 1,
+                "crab" => crabs += 1,
+                other => panic!("Unexpected bug {other}"),
+            }
+        }
+        assert!(spiders > crabs * 2, "spiders: {spiders}, crabs: {crabs}");
+        assert!(crabs > 0);
+    }
+
+    #[test]
+    fn weight_factors() {
+        let rules = rules(
+            r#"(rules: [
+                (when: Always, bug: "spider"),
+                (when: Always, bug: "crab"),
+            ])"#,
+        );
+        let ctx = SpawnContext::default();
+        let mut rnd = StdRng::seed_from_u64(404);
+        for _ in 0..100 {
+            let rule = rules
+                .choose_weighted(&ctx, &mut rnd, |r| (r.bug == "crab") as u8 as f32)
+                .unwrap();
+            assert_eq!(rule.bug, "crab");
+        }
+        assert!(rules.choose_weighted(&ctx, &mut rnd, |_| 0.).is_none());
+    }
+
+    #[test]
+    fn rule_overrides() {
+        let rules = rules(
+            r#"(rules: [(when: Always, bug: "spider", speed: Some(4.0), path: Some(Straight))])"#,
+        );
+        let mut rnd = StdRng::seed_from_u64(404);
+        let rule = rules.choose(&SpawnContext::default(), &mut rnd).unwrap();
+        assert_eq!(rule.speed, Some(4.0));
+        assert_eq!(rule.path, Some(PathStyle::Straight));
+    }
+
+    #[test]
+    fn no_rules_match() {
+        assert_eq!(choose(&SpawnRules { rules: Vec::new() }, 0, 0.0), None);
+        let zero = rules(r#"(rules: [(when: Always, bug: "spider", weight: 0.0)])"#);
+        assert_eq!(choose(&zero, 0, 0.0), None);
+    }
+
+    #[test]
+    fn default_rules_are_valid() {
+        for data in [
+            include_str!("../../assets/rules/default.rules.ron"),
+            include_str!("../../assets/rules/debug.rules.ron"),
+        ] {
+            let rules = rules(data);
+            assert_eq!(choose(&rules, 0, 0.0).as_deref(), Some("spider"));
+        }
+        let release = rules(include_str!("../../assets/rules/default.rules.ron"));
+        assert_eq!(choose(&release, 404, 0.0).as_deref(), Some("crab"));
+    }
+
+    fn rules_with(when: SpawnCondition) -> SpawnRules {
+        SpawnRules {
+            rules: vec![SpawnRule {
+                when,
+                bug: "crab".to_string(),
+                priority: 0,
+                weight: 1.0,
+                speed: None,
+                path: None,
+            }],
+        }
+    }
+}
@@ -1,21 +1,35 @@
 use std::time::Duration;
 
-use bevy::audio::VolumeLevel;
 use bevy::prelude::*;
 use bevy_mod_picking::prelude::*;
 use bevy_tweening::{Animator, EaseFunction, Tween, TweenCompleted};
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use crate::lens::GameTextSizeLens;
 use crate::{
-    effects::EffectTypeEvent, ext::Vec3ExtMut, helper::generate_points, GameState,
-    MAX_BUGS_ON_SCREEN,
+    audio::SfxEvent,
+    config::GameConfig,
+    effects::EffectTypeEvent,
+    particles::{EmitterConfig, ParticleEmitter},
+    path::PathGenerator,
+    rng::GameRng,
+    settings::Settings,
+    GameState,
 };
 
 use super::{
-    components::*, BugAnimations, BugEntityClickedEvent, BugsSpawnTimer, ScoreTextResource,
+    archetype::{BugKind, BugRegistry},
+    boss::Boss,
+    combo::*,
+    components::*,
+    difficulty::DifficultyCurve,
+    rules::{SpawnContext, SpawnRules},
+    BugEntityClickedEvent, BugEscapedEvent, BugsSpawnTimer, ScoreTextResource,
 };
 
+/// Bugs from this speed leave a trail
+const FAST_BUG_SPEED: f32 = 4.;
+
 //
 // Generation of bug entities
 //
@@ -23,70 +37,120 @@ pub(super) fn factory_bugs(
     mut cmd: Commands,
     time: Res<Time>,
     mut spawn_data: ResMut<BugsSpawnTimer>,
+    registry: Res<BugRegistry>,
+    rules: Res<Assets<SpawnRules>>,
     bugs: Query<Entity, With<BugData>>,
     score: Res<ScoreTextResource>,
+    config: Res<GameConfig>,
+    difficulty: Res<DifficultyCurve>,
+    mut rnd: ResMut<GameRng>,
 ) {
-    if !spawn_data.timer.tick(time.delta()).finished() || bugs.iter().count() >= *MAX_BUGS_ON_SCREEN
-    {
+    spawn_data.elapsed += time.delta_seconds();
+    let ctx = SpawnContext {
+        score: score.0,
+        elapsed: spawn_data.elapsed,
+    };
+    let max_bugs = difficulty.max_bugs(&ctx, config.max_bugs_on_screen);
+    if !spawn_data.timer.tick(time.delta()).finished() || bugs.iter().count() >= max_bugs {
         return;
     }
-    let mut rnd = thread_rng();
-    let points = generate_points(rnd.clone());
-    let (scene, animations) = BugAnimations::factory(score.0, &spawn_data);
-    // Spawning a cube to experiment on
-    cmd.spawn((
-        PbrBundle {
-            mesh: spawn_data.cube.clone(),
-            material: spawn_data.material.clone(),
-            transform: Transform::from_translation(points[0]).with_scale(Vec3::splat(1.)),
-            ..default()
-        },
-        PickableBundle::default(),
-        RaycastPickTarget::default(),
-        On::<Pointer<Down>>::send_event::<BugEntityClickedEvent>(),
-        BugData::factory(score.0, animations),
-        BugPathWalk {
-            points,
-            current_path: 0,
-            speed: 2.5,
-        },
-    ))
-    .with_children(|parent| {
-        parent.spawn(SceneBundle {
-            scene,
-            transform: Transform::from_translation(Vec3::new(0., -1., 0.5)),
-            ..default()
-        });
-    });
+    let Some(rules) = rules.get(&registry.rules) else { return; };
+    let Some(rule) = rules.choose_weighted(&ctx, &mut *rnd, |rule| {
+        difficulty.weight(&rule.bug, &ctx)
+    }) else { return; };
+    let Some(kind) = registry.get(&rule.bug) else {
+        log::warn!("Unknown bug archetype: {}", rule.bug);
+        return;
+    };
+    let points = rule.path.unwrap_or(kind.archetype.path).generate(&mut *rnd);
+    let speed = rule.speed.unwrap_or(kind.archetype.speed);
+    spawn_bug(&mut cmd, &spawn_data.material, kind, points, speed);
 
     // Change timer and reset
-    spawn_data
-        .timer
-        .set_duration(Duration::from_secs(rnd.gen_range(2u64..=5u64)));
+    spawn_data.timer.set_duration(Duration::from_secs_f32(
+        rnd.gen_range(difficulty.spawn_interval(&ctx)),
+    ));
     spawn_data.timer.reset();
 }
 
+/// Bug of the kind walking the points, with a trail when is fast
+pub(super) fn spawn_bug(
+    cmd: &mut Commands,
+    material: &Handle<StandardMaterial>,
+    kind: &BugKind,
+    points: Vec<Vec3>,
+    speed: f32,
+) -> Entity {
+    let mut bug = cmd.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(points[0])),
+        BugData::factory(
+            &kind.archetype,
+            kind.animations.clone(),
+            kind.sounds.clone(),
+        ),
+        BugPathWalk::new(points, speed),
+    ));
+    if let Some(boss) = &kind.archetype.boss {
+        bug.insert(Boss::new(boss));
+    }
+    bug.with_children(|parent| {
+        // invisible mesh picked by the hits
+        parent.spawn((
+            PbrBundle {
+                mesh: kind.hitbox.clone(),
+                material: material.clone(),
+                ..default()
+            },
+            PickableBundle::default(),
+            RaycastPickTarget::default(),
+            BugHitbox,
+        ));
+        parent.spawn(SceneBundle {
+            scene: kind.model.clone_weak(),
+            transform: Transform::from_translation(Vec3::new(0., -1., 0.5))
+                .with_scale(Vec3::splat(kind.archetype.scale)),
+            ..default()
+        });
+        if speed >= FAST_BUG_SPEED {
+            parent.spawn((
+                TransformBundle::default(),
+                ParticleEmitter::new(EmitterConfig::trail()),
+            ));
+        }
+    })
+    .id()
+}
+
 //
 // Movement of bug entity on screen
 //
 pub(super) fn movement_bugs(
     mut cmd: Commands,
     time: Res<Time>,
+    settings: Res<Settings>,
+    difficulty: Res<DifficultyCurve>,
+    score: Res<ScoreTextResource>,
+    spawn_data: Res<BugsSpawnTimer>,
     mut bugs: Query<(Entity, &mut BugData, &mut Transform, &mut BugPathWalk)>,
+    mut escaped: EventWriter<BugEscapedEvent>,
 ) {
+    let speed = difficulty.speed(&SpawnContext {
+        score: score.0,
+        elapsed: spawn_data.elapsed,
+    }) * settings.speed_scale;
     for (entity, mut data, mut transform, mut path) in bugs.iter_mut() {
-        if data.is_dead() {
+        if data.is_dead() || data.state == BugState::Hurt {
             continue;
         }
         if data.state == BugState::Idle {
             data.state = BugState::Walking;
         }
-        if let Some(next) = path.points.get(path.current_path + 1) {
-            transform.look_at(path.points[path.current_path], Vec3::Z);
-            if transform.move_towards(next, path.speed * time.delta_seconds()) {
-                path.current_path += 1;
-            }
+        path.distance += path.speed * speed * time.delta_seconds();
+        if let Some((pos, direction)) = path.path.sample(path.distance) {
+            transform.translation = pos;
+            transform.look_at(pos - direction, Vec3::Z);
         } else {
+            escaped.send(BugEscapedEvent);
             cmd.entity(entity).despawn_recursive();
         }
     }
@@ -111,6 +175,11 @@ pub(super) fn animate_bugs(
                             .set_speed(1.5)
                             .repeat();
                     }
+                    BugState::Enraged => {
+                        anim.play(data.animations.walk.clone_weak())
+                            .set_speed(2.5)
+                            .repeat();
+                    }
                     BugState::Death => {
                         anim.stop_repeating()
                             .play_with_transition(
@@ -132,35 +201,56 @@ pub(super) fn animate_bugs(
 pub(super) fn kill_detect(
     mut cmd: Commands,
     time: Res<Time>,
-    spawn_data: Res<BugsSpawnTimer>,
+    settings: Res<Settings>,
     text: Query<Entity, With<ScoreText>>,
     mut bugs: Query<(Entity, &Transform, &mut BugData), With<BugPathWalk>>,
     mut score: ResMut<ScoreTextResource>,
+    mut combo: ResMut<Combo>,
     mut click_event: EventReader<BugEntityClickedEvent>,
     mut effect: EventWriter<EffectTypeEvent>,
+    mut killed: EventWriter<BugKilledEvent>,
+    mut combo_event: EventWriter<ComboEvent>,
+    mut sfx: EventWriter<SfxEvent>,
 ) {
     let clicks = click_event.iter().collect::<Vec<&BugEntityClickedEvent>>();
-    let score_entity = text.single();
+    let score_entity = text.get_single().ok();
 
     for (entity, bug_transform, mut data) in bugs.iter_mut() {
         // if bug is killed
         if data.is_dead() {
-            let mut entity = cmd.entity(entity);
             // play dead animation
             if data.state != BugState::Death {
-                score.0 += 1;
+                let level = combo.level();
+                let points = combo.kill(data.score);
+                score.0 += points;
+                combo_event.send(ComboEvent {
+                    points,
+                    streak: combo.streak,
+                    level_up: combo.level() > level,
+                    pos: bug_transform.translation,
+                });
                 data.state = BugState::Death;
-                entity
-                    .remove::<PickableBundle>()
-                    .remove::<RaycastPickTarget>()
-                    .remove::<On<Pointer<Click>>>();
+                if let Some(death) = data.sounds.death.as_ref() {
+                    sfx.send(
+                        SfxEvent::new(death.clone())
+                            .with_volume(0.5)
+                            .at(bug_transform.translation),
+                    );
+                }
+                killed.send(BugKilledEvent {
+                    archetype: data.archetype.clone(),
+                    pos: bug_transform.translation,
+                });
             }
             // run countdown for remove from scene
             if data.wait_for_remove.tick(time.delta()).finished() {
-                effect.send(EffectTypeEvent::Dead {
-                    pos: bug_transform.translation,
-                });
-                entity.despawn_recursive();
+                effect.send(EffectTypeEvent::named_or(
+                    data.death_effect.as_ref(),
+                    EffectTypeEvent::Dead {
+                        pos: bug_transform.translation,
+                    },
+                ));
+                cmd.entity(entity).despawn_recursive();
             }
             continue;
         }
@@ -170,18 +260,13 @@ pub(super) fn kill_detect(
                 continue;
             }
             data.clicks += 1;
-            cmd.spawn(AudioBundle {
-                source: spawn_data.click_audio.clone(),
-                settings: PlaybackSettings::DESPAWN
-                    .with_volume(bevy::audio::Volume::Relative(VolumeLevel::new(0.5))),
-            });
-            // Spawn Score Bounce animation
-            {
+            // Spawn Score Bounce animation, bigger with the combo
+            if let Some(score_entity) = score_entity.filter(|_| !settings.reduced_motion) {
                 let mut score_entity = cmd.entity(score_entity);
                 let tween = Tween::new(
                     EaseFunction::BounceOut,
                     Duration::from_secs_f32(0.6),
-                    GameTextSizeLens::create(128., 165.),
+                    GameTextSizeLens::create(128., 165. + 15. * combo.level() as f32),
                 );
                 score_entity.remove::<Animator<Text>>();
                 score_entity.insert(Animator::new(tween));
@@ -192,7 +277,15 @@ pub(super) fn kill_detect(
             } else {
                 bug_transform.translation
             };
-            effect.send(EffectTypeEvent::Click { pos });
+            sfx.send(
+                SfxEvent::new(data.sounds.hit.clone())
+                    .with_volume(0.5)
+                    .at(pos),
+            );
+            effect.send(EffectTypeEvent::named_or(
+                data.hit_effect.as_ref(),
+                EffectTypeEvent::Click { pos },
+            ));
         }
     }
 }
@@ -204,7 +297,7 @@ pub(super) fn score_print(
     mut text: Query<&mut Text, With<ScoreText>>,
     score: Res<ScoreTextResource>,
 ) {
-    let mut text = text.single_mut();
+    let Ok(mut text) = text.get_single_mut() else { return; };
     text.sections[0].value = score.0.to_string();
 }
 
@@ -0,0 +1,599 @@
+use bevy::{
+    audio::Volume,
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+    window::WindowFocused,
+};
+
+use crate::{
+    app_headless,
+    audio::{AudioBus, MusicKind, MusicTrack},
+    input::{ActionEvent, GameAction},
+    locale::{LocaleChanged, LocalizedText},
+    particles::Particle,
+    settings::Settings,
+    stats::{Accuracy, PlayerStats},
+    storage::StorageResource,
+    Difficulty, GameConfig, GameMode, GameRng, GameState,
+};
+
+use super::{
+    boss::{Boss, HealthBar},
+    combo::Combo,
+    components::*,
+    difficulty::{Curve, DifficultyCurve},
+    waves::Waves,
+    BugEntityClickedEvent, MissEvent, PauseEvent, RestartEvent, Round, ScoreTextResource,
+};
+
+const MAX_BUGS: usize = 3;
+
+fn game() -> App {
+    game_with(GameConfig::default())
+}
+
+fn game_with(config: GameConfig) -> App {
+    let mut app = app_headless("en-EN", config.with_max_bugs_on_screen(MAX_BUGS));
+    app.finish();
+    app.cleanup();
+    app.update();
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Game);
+    app
+}
+
+/// Frames of `update_until`, a minute of the game clock
+const MAX_FRAMES: u32 = 60 * 60;
+
+/// Step frames until the condition is true, the assets are loaded in background
+#[track_caller]
+fn update_until(app: &mut App, mut done: impl FnMut(&mut World) -> bool) {
+    for _ in 0..MAX_FRAMES {
+        app.update();
+        if done(&mut app.world) {
+            return;
+        }
+    }
+    panic!("The condition was not reached after {MAX_FRAMES} frames");
+}
+
+fn update_secs(app: &mut App, secs: u32) {
+    for _ in 0..secs * 60 {
+        app.update();
+    }
+}
+
+fn bugs(world: &mut World) -> Vec<Entity> {
+    world
+        .query_filtered::<Entity, With<BugData>>()
+        .iter(world)
+        .collect()
+}
+
+/// Fade of the music tracks playing, the page one first
+fn music(world: &mut World) -> Vec<(MusicKind, f32)> {
+    let mut tracks = world
+        .query::<&MusicTrack>()
+        .iter(world)
+        .map(|track| (track.kind, track.fade))
+        .collect::<Vec<_>>();
+    tracks.sort_by_key(|(kind, _)| *kind == MusicKind::Game);
+    tracks
+}
+
+fn score_text(world: &mut World) -> Option<String> {
+    world
+        .query_filtered::<&Text, With<ScoreText>>()
+        .get_single(world)
+        .ok()
+        .map(|text| text.sections[0].value.clone())
+}
+
+#[test]
+fn spawn_bugs_until_max() {
+    let mut app = game();
+    update_until(&mut app, |world| !bugs(world).is_empty());
+
+    for _ in 0..30 {
+        update_secs(&mut app, 1);
+        assert!(bugs(&mut app.world).len() <= MAX_BUGS);
+    }
+}
+
+#[test]
+fn default_difficulty_spawns_up_to_the_config_max() {
+    let max = 12;
+    let mut app = game();
+    app.world.resource_mut::<GameConfig>().max_bugs_on_screen = max;
+    app.update();
+    // faster spawns, the max of the default difficulty is kept
+    let mut difficulty = app.world.resource_mut::<DifficultyCurve>();
+    difficulty.spawn_interval = (Curve::constant(0.1), Curve::constant(0.1));
+    update_until(&mut app, |world| bugs(world).len() >= max);
+    update_secs(&mut app, 1);
+    assert!(bugs(&mut app.world).len() <= max);
+}
+
+#[test]
+fn click_kills_bug_and_scores() {
+    let mut app = game();
+    update_until(&mut app, |world| !bugs(world).is_empty());
+
+    let bug = bugs(&mut app.world)[0];
+    let (max_clicks, score) = {
+        let data = app.world.get::<BugData>(bug).unwrap();
+        (data.max_clicks, data.score)
+    };
+    for _ in 0..max_clicks {
+        app.world.send_event(BugEntityClickedEvent(bug, None));
+        app.update();
+    }
+    app.update();
+
+    assert!(app.world.get::<BugData>(bug).unwrap().is_dead());
+    assert_eq!(app.world.resource::<ScoreTextResource>().0, score);
+    app.update();
+    let stats = app.world.resource::<PlayerStats>();
+    let archetype = &app.world.get::<BugData>(bug).unwrap().archetype;
+    assert_eq!(stats.total_kills, 1);
+    assert_eq!(stats.best_score, score);
+    assert_eq!(stats.sessions, 1);
+    assert_eq!(stats.kills.get(archetype), Some(&1));
+    update_until(&mut app, |world| {
+        score_text(world) == Some(score.to_string())
+    });
+
+    // the body is removed after the death animation
+    update_secs(&mut app, 4);
+    assert!(app.world.get_entity(bug).is_none());
+}
+
+fn saved_stats(app: &App) -> PlayerStats {
+    let saved = app.world.resource::<StorageResource>().0.load("stats");
+    ron::from_str(&saved.unwrap()).unwrap()
+}
+
+#[test]
+fn stats_are_saved_on_pause_not_on_every_kill() {
+    let mut app = game();
+    update_until(&mut app, |world| !bugs(world).is_empty());
+    // the session is saved when the game starts
+    assert_eq!(saved_stats(&app).sessions, 1);
+
+    let bug = bugs(&mut app.world)[0];
+    let max_clicks = app.world.get::<BugData>(bug).unwrap().max_clicks;
+    for _ in 0..max_clicks {
+        app.world.send_event(BugEntityClickedEvent(bug, None));
+        app.update();
+    }
+    app.update();
+    assert_eq!(app.world.resource::<PlayerStats>().total_kills, 1);
+    assert_eq!(saved_stats(&app).total_kills, 0);
+
+    app.world.send_event(PauseEvent::Pause);
+    app.update();
+    app.update();
+    assert_eq!(state(&app), &GameState::Paused);
+    assert_eq!(saved_stats(&app).total_kills, 1);
+}
+
+#[test]
+fn hits_play_on_the_sfx_bus() {
+    let mut app = game();
+    update_until(&mut app, |world| !bugs(world).is_empty());
+    app.world
+        .resource_mut::<Settings>()
+        .change_volume(AudioBus::Sfx, -5);
+
+    let bug = bugs(&mut app.world)[0];
+    app.world.send_event(BugEntityClickedEvent(bug, None));
+    app.update();
+    app.update();
+
+    let volumes = app
+        .world
+        .query_filtered::<&PlaybackSettings, Without<MusicTrack>>()
+        .iter(&app.world)
+        .filter_map(|settings| match settings.volume {
+            Volume::Absolute(level) => Some(level.get()),
+            Volume::Relative(_) => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(volumes, vec![0.25]);
+}
+
+#[test]
+fn page_waits_the_seconds_of_the_config_asset() {
+    // `game.config.ron` waits 15 seconds, plus 8 of the fades
+    let config = GameConfig::default()
+        .with_time_wait_to_start(0)
+        .with_asset("game.config.ron");
+    let mut app = app_headless("en-EN", config);
+    app.finish();
+    app.cleanup();
+    update_until(&mut app, |world| {
+        world.resource::<GameConfig>().time_wait_to_start == 15
+    });
+
+    update_secs(&mut app, 12);
+    assert_eq!(state(&app), &GameState::MainPage);
+    update_secs(&mut app, 15);
+    assert_eq!(state(&app), &GameState::Game);
+}
+
+#[test]
+fn music_crossfades_from_page_to_game() {
+    let mut app = app_headless("en-EN", GameConfig::default());
+    app.finish();
+    app.cleanup();
+    update_secs(&mut app, 3);
+    assert_eq!(music(&mut app.world), vec![(MusicKind::Page, 1.)]);
+
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Game);
+    app.update();
+    update_secs(&mut app, 1);
+    let tracks = music(&mut app.world);
+    assert_eq!(tracks.len(), 2);
+    let ((page, out), (game, fade_in)) = (tracks[0], tracks[1]);
+    assert_eq!((page, game), (MusicKind::Page, MusicKind::Game));
+    assert!(out < 1. && fade_in > 0.);
+    assert!((out + fade_in - 1.).abs() < 0.05, "{out} + {fade_in}");
+
+    // the page track is removed once silent
+    update_secs(&mut app, 2);
+    assert_eq!(music(&mut app.world), vec![(MusicKind::Game, 1.)]);
+}
+
+#[test]
+fn speed_scale_slows_down_the_bugs() {
+    let mut app = game();
+    app.world.resource_mut::<Settings>().change_speed_scale(-5);
+    update_until(&mut app, |world| !bugs(world).is_empty());
+
+    let bug = bugs(&mut app.world)[0];
+    let walked = |app: &App| app.world.get::<BugPathWalk>(bug).unwrap().distance;
+    let start = walked(&app);
+    update_secs(&mut app, 1);
+    let speed = app.world.get::<BugPathWalk>(bug).unwrap().speed;
+    assert!((walked(&app) - start - speed * 0.5).abs() < 1e-3);
+}
+
+#[test]
+fn hard_difficulty_speeds_up_the_bugs() {
+    let mut app = game_with(GameConfig::default().with_difficulty(Difficulty::Hard));
+    update_until(&mut app, |world| !bugs(world).is_empty());
+
+    let bug = bugs(&mut app.world)[0];
+    let walked = |app: &App| app.world.get::<BugPathWalk>(bug).unwrap().distance;
+    let start = walked(&app);
+    update_secs(&mut app, 1);
+    let speed = app.world.get::<BugPathWalk>(bug).unwrap().speed;
+    assert!((walked(&app) - start - speed * 1.2).abs() < 1e-3);
+}
+
+fn kill_first_bug(app: &mut App) {
+    let bug = bugs(&mut app.world)[0];
+    let max_clicks = app.world.get::<BugData>(bug).unwrap().max_clicks;
+    for _ in 0..max_clicks {
+        app.world.send_event(BugEntityClickedEvent(bug, None));
+        app.update();
+    }
+    app.update();
+}
+
+fn particles(world: &mut World) -> usize {
+    world.query::<&Particle>().iter(world).count()
+}
+
+#[test]
+fn killed_bugs_burst_into_debris() {
+    let mut app = game();
+    update_until(&mut app, |world| !bugs(world).is_empty());
+    kill_first_bug(&mut app);
+    assert!(particles(&mut app.world) > 0);
+
+    // the debris fades before the body is removed
+    update_secs(&mut app, 1);
+    assert_eq!(particles(&mut app.world), 0);
+}
+
+#[test]
+fn reduced_motion_has_no_debris() {
+    let mut app = game();
+    app.world.resource_mut::<Settings>().reduced_motion = true;
+    update_until(&mut app, |world| !bugs(world).is_empty());
+    kill_first_bug(&mut app);
+    assert_eq!(particles(&mut app.world), 0);
+}
+
+#[test]
+fn clicks_on_other_entities_are_ignored() {
+    let mut app = game();
+    update_until(&mut app, |world| !bugs(world).is_empty());
+
+    let bug = bugs(&mut app.world)[0];
+    let other = app.world.spawn_empty().id();
+    app.world.send_event(BugEntityClickedEvent(other, None));
+    app.update();
+    app.update();
+
+    assert_eq!(app.world.get::<BugData>(bug).unwrap().clicks, 0);
+    assert_eq!(app.world.resource::<ScoreTextResource>().0, 0);
+}
+
+#[test]
+fn same_seed_same_bugs() {
+    let first_bug = |seed: u64| {
+        let mut app = game_with(GameConfig::default().with_seed(seed));
+        update_until(&mut app, |world| !bugs(world).is_empty());
+        let bug = bugs(&mut app.world)[0];
+        let path = app.world.get::<BugPathWalk>(bug).unwrap();
+        (path.path.points().to_vec(), path.speed)
+    };
+
+    assert_eq!(first_bug(404), first_bug(404));
+    assert_ne!(first_bug(404).0, first_bug(405).0);
+}
+
+#[test]
+fn seed_changed_at_runtime_is_applied() {
+    let mut app = game();
+    app.world.resource_mut::<GameConfig>().seed = Some(404);
+    app.update();
+    assert_eq!(app.world.resource::<GameRng>().seed(), 404);
+}
+
+#[test]
+fn timed_round_ends_and_restarts() {
+    let mut app = game_with(GameConfig::default().with_mode(GameMode::Timed(1.)));
+    update_secs(&mut app, 2);
+    assert_eq!(
+        app.world.resource::<State<GameState>>().get(),
+        &GameState::GameOver
+    );
+
+    app.world.send_event(RestartEvent);
+    app.update();
+    app.update();
+    assert_eq!(
+        app.world.resource::<State<GameState>>().get(),
+        &GameState::Game
+    );
+    assert_eq!(app.world.resource::<ScoreTextResource>().0, 0);
+    assert!(!app.world.resource::<Round>().is_over());
+    assert!(bugs(&mut app.world).is_empty());
+}
+
+#[test]
+fn restart_reseeds_only_without_configured_seed() {
+    let seed_after_restart = |config: GameConfig| {
+        let mut app = game_with(config);
+        let seed = app.world.resource::<GameRng>().seed();
+        app.world.send_event(RestartEvent);
+        app.update();
+        (seed, app.world.resource::<GameRng>().seed())
+    };
+
+    assert_eq!(
+        seed_after_restart(GameConfig::default().with_seed(404)),
+        (404, 404)
+    );
+    let (before, after) = seed_after_restart(GameConfig::default());
+    assert_ne!(before, after);
+}
+
+#[test]
+fn focus_lost_pauses_the_game() {
+    let mut app = game();
+    update_until(&mut app, |world| !bugs(world).is_empty());
+
+    let window = app.world.spawn_empty().id();
+    app.world.send_event(WindowFocused {
+        window,
+        focused: false,
+    });
+    app.update();
+    app.update();
+    assert_eq!(
+        app.world.resource::<State<GameState>>().get(),
+        &GameState::Paused
+    );
+
+    // the focus back doesn't resume, the player does
+    app.world.send_event(WindowFocused {
+        window,
+        focused: true,
+    });
+    // nothing moves while is paused
+    let bug = bugs(&mut app.world)[0];
+    let distance = app.world.get::<BugPathWalk>(bug).unwrap().distance;
+    update_secs(&mut app, 1);
+    assert_eq!(
+        app.world.get::<BugPathWalk>(bug).unwrap().distance,
+        distance
+    );
+
+    app.world.send_event(PauseEvent::Resume);
+    app.update();
+    update_secs(&mut app, 1);
+    assert_eq!(
+        app.world.resource::<State<GameState>>().get(),
+        &GameState::Game
+    );
+    assert!(app.world.get::<BugPathWalk>(bug).unwrap().distance > distance);
+}
+
+fn state(app: &App) -> &GameState {
+    app.world.resource::<State<GameState>>().get()
+}
+
+#[test]
+fn keys_and_actions_pause_and_resume() {
+    let mut app = game();
+    update_until(&mut app, |world| !bugs(world).is_empty());
+
+    let window = app.world.spawn_empty().id();
+    app.world.send_event(KeyboardInput {
+        scan_code: 0,
+        key_code: Some(KeyCode::Escape),
+        state: ButtonState::Pressed,
+        window,
+    });
+    app.update();
+    app.update();
+    assert_eq!(state(&app), &GameState::Paused);
+
+    app.world.send_event(ActionEvent::new(GameAction::Confirm));
+    for _ in 0..3 {
+        app.update();
+    }
+    assert_eq!(state(&app), &GameState::Game);
+}
+
+#[test]
+fn miss_breaks_combo_and_accuracy() {
+    let mut app = game();
+    update_until(&mut app, |world| !bugs(world).is_empty());
+
+    let bug = bugs(&mut app.world)[0];
+    app.world.send_event(BugEntityClickedEvent(bug, None));
+    app.update();
+    app.world.send_event(MissEvent { pos: Vec3::ZERO });
+    app.update();
+    app.update();
+
+    let accuracy = app.world.resource::<Accuracy>();
+    assert_eq!((accuracy.hits, accuracy.misses), (1, 1));
+    assert_eq!(accuracy.ratio(), 0.5);
+    assert_eq!(app.world.resource::<Combo>().streak, 0);
+    let stats = app.world.resource::<PlayerStats>();
+    assert_eq!((stats.hits, stats.misses), (1, 1));
+
+    let miss: Handle<AudioSource> = app
+        .world
+        .resource::<AssetServer>()
+        .get_handle("audio/miss.wav");
+    let mut sounds = app
+        .world
+        .query_filtered::<&Handle<AudioSource>, Without<MusicTrack>>();
+    assert!(sounds.iter(&app.world).any(|sound| *sound == miss));
+}
+
+fn localized_text(world: &mut World, key: &str) -> Option<String> {
+    world
+        .query::<(&LocalizedText, &Text)>()
+        .iter(world)
+        .find(|(localized, _)| localized.key == key)
+        .map(|(localized, text)| text.sections[localized.section].value.clone())
+}
+
+#[test]
+fn change_language_updates_texts() {
+    let mut app = game();
+    update_until(&mut app, |world| {
+        localized_text(world, "message2").as_deref() == Some("Patience is a great virtue")
+    });
+
+    app.world.send_event(LocaleChanged("es-MX".into()));
+    update_until(&mut app, |world| {
+        localized_text(world, "message2").as_deref() == Some("La paciencia es una gran virtud")
+    });
+}
+
+#[test]
+fn loaded_settings_are_not_saved_again() {
+    let mut app = game();
+    update_until(&mut app, |world| {
+        localized_text(world, "message2").is_some()
+    });
+    let saved = app.world.resource::<StorageResource>().0.load("settings");
+    assert_eq!(saved, None);
+}
+
+#[test]
+fn settings_are_applied_and_saved() {
+    let mut app = game();
+    update_until(&mut app, |world| {
+        localized_text(world, "message2").is_some()
+    });
+
+    let mut settings = app.world.resource_mut::<Settings>();
+    settings.language = Some("es-ES".into());
+    settings.change_volume(AudioBus::Master, -5);
+    update_until(&mut app, |world| {
+        localized_text(world, "message2").as_deref() == Some("La paciencia es una gran virtud")
+    });
+
+    let saved = app.world.resource::<StorageResource>().0.load("settings");
+    let saved = ron::from_str::<Settings>(&saved.unwrap()).unwrap();
+    assert_eq!(saved.language, Some("es-ES".into()));
+    assert_eq!(saved.volume, 0.5);
+}
+
+#[test]
+fn level_waves_start_once_the_previous_is_cleared() {
+    let mut app = game_with(GameConfig::default().with_level("levels/default.level.ron"));
+    update_until(&mut app, |world| !bugs(world).is_empty());
+    assert_eq!(app.world.resource::<Waves>().wave, 1);
+    update_until(&mut app, |world| {
+        localized_text(world, "wave").as_deref() == Some("Wave 1")
+    });
+
+    update_until(&mut app, |world| {
+        for bug in bugs(world) {
+            world.send_event(BugEntityClickedEvent(bug, None));
+        }
+        world.resource::<Waves>().wave == 2
+    });
+    assert!(!app.world.resource::<Round>().cleared);
+}
+
+#[test]
+fn level_waves_keep_the_max_bugs_on_screen() {
+    let mut app = game_with(GameConfig::default().with_level("levels/default.level.ron"));
+    update_until(&mut app, |world| bugs(world).len() == MAX_BUGS);
+    // the first wave has more bugs than the max
+    for _ in 0..60 * 5 {
+        app.update();
+        assert!(bugs(&mut app.world).len() <= MAX_BUGS);
+    }
+}
+
+#[test]
+fn bosses_flash_split_and_show_their_health() {
+    let mut app = game_with(GameConfig::default().with_level("levels/boss.level.ron"));
+    update_until(&mut app, |world| !bugs(world).is_empty());
+    let boss = bugs(&mut app.world)[0];
+    assert!(app.world.get::<Boss>(boss).is_some());
+    update_until(&mut app, |world| {
+        world.query::<&HealthBar>().iter(world).count() == 1
+    });
+
+    app.world.send_event(BugEntityClickedEvent(boss, None));
+    app.update();
+    assert_eq!(
+        app.world.get::<BugData>(boss).unwrap().state,
+        BugState::Hurt
+    );
+    // the first phase starts under the 66% of the health
+    let max_clicks = app.world.get::<BugData>(boss).unwrap().max_clicks;
+    while app.world.get::<BugData>(boss).unwrap().health() > 0.66 {
+        app.world.send_event(BugEntityClickedEvent(boss, None));
+        app.update();
+    }
+    app.update();
+    assert_eq!(app.world.get::<Boss>(boss).unwrap().phase, 1);
+    assert_eq!(bugs(&mut app.world).len(), 4);
+    assert!(!app.world.get::<BugData>(boss).unwrap().is_dead());
+
+    for _ in 0..max_clicks {
+        app.world.send_event(BugEntityClickedEvent(boss, None));
+        app.update();
+    }
+    app.update();
+    assert!(app.world.get::<BugData>(boss).unwrap().is_dead());
+    assert_eq!(app.world.query::<&HealthBar>().iter(&app.world).count(), 0);
+}
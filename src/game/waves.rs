@@ -0,0 +1,377 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, reflect::TypeUuid, ui::widget::Label};
+use bevy_tweening::{Animator, Delay, EaseFunction, Tween};
+use rand::{distributions::WeightedIndex, prelude::Distribution};
+use serde::Deserialize;
+
+use crate::{
+    config::GameConfig,
+    data::RonAsset,
+    lens::ThemeAlphaLens,
+    locale::{LocaleArgs, LocalizedText},
+    path::PathStyle,
+    rng::GameRng,
+    ui::{ThemeColor, ThemeRole},
+};
+
+use super::{
+    archetype::BugRegistry, components::*, difficulty::DifficultyCurve, round::Round,
+    rules::SpawnContext, systems::spawn_bug, BugsSpawnTimer, ScoreTextResource,
+};
+
+/// Seconds the wave banner is on screen
+const BANNER_SECS: f32 = 2.5;
+
+//
+// Group of bugs spawned one after other
+//
+#[derive(Clone, Debug, Deserialize, Reflect)]
+pub struct Wave {
+    /// Bugs of the wave
+    pub count: u32,
+    /// Archetypes with the chance to be selected, like `[("spider", 3.0), ("crab", 1.0)]`
+    pub bugs: Vec<(String, f32)>,
+    /// Seconds between two bugs
+    pub cadence: f32,
+    /// Path of the bugs, the archetype one when is not set
+    #[serde(default)]
+    pub path: Option<PathStyle>,
+    /// Seconds to wait once the previous wave is cleared
+    #[serde(default = "default_delay")]
+    pub delay: f32,
+}
+
+fn default_delay() -> f32 {
+    2.
+}
+
+//
+// Scripted level, the round ends when the last wave is cleared
+//
+#[derive(Debug, Deserialize, Reflect, TypeUuid)]
+#[uuid = "a3f61c8e-2b94-4d07-9e5a-7c1d8b4f0e63"]
+pub struct Level {
+    pub waves: Vec<Wave>,
+}
+
+impl RonAsset for Level {
+    const EXTENSIONS: &'static [&'static str] = &["level.ron"];
+}
+
+#[derive(Clone, Debug, Default)]
+enum WaveState {
+    /// The delay of the next wave is not started
+    #[default]
+    Next,
+    Delay(Timer),
+    /// Bugs left to spawn
+    Spawning {
+        left: u32,
+        timer: Timer,
+    },
+    /// Every bug is spawned, waiting until all are dead or escaped
+    Clearing,
+    Cleared,
+}
+
+//
+// Progress of the level of `GameConfig::level`
+//
+#[derive(Resource, Default)]
+pub(super) struct Waves {
+    pub(super) level: Handle<Level>,
+    /// Number of the current wave, 0 before the first one
+    pub(super) wave: usize,
+    state: WaveState,
+}
+
+impl Waves {
+    pub fn restart(&mut self) {
+        self.wave = 0;
+        self.state = WaveState::Next;
+    }
+
+    /// Advance the waves, returns the wave to spawn a bug of,
+    /// the spawns wait while the screen is `full`
+    fn tick<'a>(
+        &mut self,
+        level: &'a Level,
+        delta: Duration,
+        alive: bool,
+        full: bool,
+    ) -> Option<&'a Wave> {
+        match &mut self.state {
+            WaveState::Next => {
+                self.state = match level.waves.get(self.wave) {
+                    Some(wave) => {
+                        WaveState::Delay(Timer::from_seconds(wave.delay, TimerMode::Once))
+                    }
+                    None => WaveState::Cleared,
+                };
+            }
+            WaveState::Delay(timer) => {
+                if timer.tick(delta).finished() {
+                    self.wave += 1;
+                    self.state = WaveState::Spawning {
+                        left: level.waves[self.wave - 1].count,
+                        // the first bug is spawned right away
+                        timer: Timer::from_seconds(0., TimerMode::Once),
+                    };
+                }
+            }
+            WaveState::Spawning { left: 0, .. } => self.state = WaveState::Clearing,
+            WaveState::Spawning { left, timer } => {
+                // the finished timer is kept until there is room
+                if timer.tick(delta).finished() && !full {
+                    let wave = &level.waves[self.wave - 1];
+                    *left -= 1;
+                    timer.set_duration(Duration::from_secs_f32(wave.cadence));
+                    timer.reset();
+                    return Some(wave);
+                }
+            }
+            WaveState::Clearing if !alive => {
+                self.state = if self.wave < level.waves.len() {
+                    WaveState::Next
+                } else {
+                    WaveState::Cleared
+                };
+            }
+            WaveState::Clearing | WaveState::Cleared => {}
+        }
+        None
+    }
+
+    pub fn is_cleared(&self) -> bool {
+        matches!(self.state, WaveState::Cleared)
+    }
+}
+
+#[derive(Component)]
+pub(super) struct WaveBanner {
+    timer: Timer,
+}
+
+//
+// Generation of bugs from the waves of the level, instead of the spawn rules,
+// with the max bugs of `factory_bugs`
+//
+pub(super) fn wave_bugs(
+    mut cmd: Commands,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    asset_server: Res<AssetServer>,
+    levels: Res<Assets<Level>>,
+    registry: Res<BugRegistry>,
+    bugs: Query<&BugData>,
+    score: Res<ScoreTextResource>,
+    difficulty: Res<DifficultyCurve>,
+    mut waves: ResMut<Waves>,
+    mut round: ResMut<Round>,
+    mut spawn_data: ResMut<BugsSpawnTimer>,
+    mut rnd: ResMut<GameRng>,
+) {
+    if config.is_changed() {
+        let Some(path) = &config.level else { return; };
+        let level = asset_server.load(path.as_str());
+        if level != waves.level {
+            waves.level = level;
+            waves.restart();
+        }
+    }
+    spawn_data.elapsed += time.delta_seconds();
+    let Some(level) = levels.get(&waves.level) else { return; };
+    let ctx = SpawnContext {
+        score: score.0,
+        elapsed: spawn_data.elapsed,
+    };
+    let alive = bugs.iter().any(|data| !data.is_dead());
+    let full = bugs.iter().count() >= difficulty.max_bugs(&ctx, config.max_bugs_on_screen);
+    let wave = waves.tick(level, time.delta(), alive, full);
+    round.cleared = waves.is_cleared();
+    let Some(wave) = wave else { return; };
+    let Ok(weights) = WeightedIndex::new(wave.bugs.iter().map(|(_, weight)| *weight)) else {
+        log::warn!("Wave without bugs to spawn: {wave:?}");
+        return;
+    };
+    let name = &wave.bugs[weights.sample(&mut *rnd)].0;
+    let Some(kind) = registry.get(name) else {
+        log::warn!("Unknown bug archetype: {name}");
+        return;
+    };
+    let points = wave.path.unwrap_or(kind.archetype.path).generate(&mut *rnd);
+    spawn_bug(
+        &mut cmd,
+        &spawn_data.material,
+        kind,
+        points,
+        kind.archetype.speed,
+    );
+}
+
+//
+// Banner with the number of the wave when it starts
+//
+pub(super) fn wave_banner(
+    mut cmd: Commands,
+    asset_server: Res<AssetServer>,
+    waves: Res<Waves>,
+    mut last: Local<usize>,
+) {
+    if waves.wave == *last {
+        return;
+    }
+    *last = waves.wave;
+    if waves.wave == 0 {
+        return;
+    }
+    let fade = Duration::from_secs_f32(0.5);
+    cmd.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/Lato-Regular.ttf"),
+                    font_size: 72.,
+                    color: Color::NONE,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                top: Val::Percent(30.),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+        Label,
+        LocalizedText::new("wave").with_args(LocaleArgs::new().with("wave", waves.wave)),
+        WaveBanner {
+            timer: Timer::from_seconds(BANNER_SECS, TimerMode::Once),
+        },
+        ThemeColor::transparent(ThemeRole::Text),
+        Animator::new(
+            Tween::new(
+                EaseFunction::QuadraticOut,
+                fade,
+                ThemeAlphaLens::create(0., 1.),
+            )
+            .then(Delay::new(Duration::from_secs_f32(BANNER_SECS) - fade * 2))
+            .then(Tween::new(
+                EaseFunction::QuadraticIn,
+                fade,
+                ThemeAlphaLens::create(1., 0.),
+            )),
+        ),
+    ));
+}
+
+pub(super) fn remove_wave_banner(
+    mut cmd: Commands,
+    time: Res<Time>,
+    mut banners: Query<(Entity, &mut WaveBanner)>,
+) {
+    for (entity, mut banner) in banners.iter_mut() {
+        if banner.timer.tick(time.delta()).finished() {
+            cmd.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub(super) fn has_level(config: Res<GameConfig>) -> bool {
+    config.level.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level() -> Level {
+        ron::from_str(include_str!("../../assets/levels/default.level.ron")).unwrap()
+    }
+
+    /// Steps of 0.1 seconds until the waves spawn a bug, at most 10 minutes
+    fn next_bug(waves: &mut Waves, level: &Level, alive: bool) -> Option<(usize, f32)> {
+        for step in 0..6000 {
+            if waves
+                .tick(level, Duration::from_millis(100), alive, false)
+                .is_some()
+            {
+                return Some((waves.wave, step as f32 / 10.));
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn default_level_is_valid() {
+        let level = level();
+        assert!(!level.waves.is_empty());
+        for wave in &level.waves {
+            assert!(wave.count > 0);
+            assert!(wave.bugs.iter().any(|(_, weight)| *weight > 0.));
+        }
+    }
+
+    #[test]
+    fn waves_spawn_their_bugs_with_cadence() {
+        let level = level();
+        let first = &level.waves[0];
+        let mut waves = Waves::default();
+        let (wave, _) = next_bug(&mut waves, &level, false).unwrap();
+        assert_eq!(wave, 1);
+        for _ in 1..first.count {
+            let (wave, secs) = next_bug(&mut waves, &level, true).unwrap();
+            assert_eq!(wave, 1);
+            assert!((secs - first.cadence).abs() < 0.15, "{secs}");
+        }
+    }
+
+    #[test]
+    fn next_wave_waits_for_the_alive_bugs() {
+        let level = level();
+        let mut waves = Waves::default();
+        for _ in 0..level.waves[0].count {
+            next_bug(&mut waves, &level, true).unwrap();
+        }
+        assert_eq!(next_bug(&mut waves, &level, true), None);
+        assert_eq!(waves.wave, 1);
+
+        let (wave, _) = next_bug(&mut waves, &level, false).unwrap();
+        assert_eq!(wave, 2);
+    }
+
+    #[test]
+    fn full_screen_holds_the_spawns() {
+        let level = level();
+        let mut waves = Waves::default();
+        next_bug(&mut waves, &level, false).unwrap();
+        for _ in 0..100 {
+            let full = waves.tick(&level, Duration::from_millis(100), true, true);
+            assert!(full.is_none());
+        }
+        // the held bug is spawned right away
+        let wave = waves.tick(&level, Duration::ZERO, true, false);
+        assert!(wave.is_some());
+    }
+
+    #[test]
+    fn level_is_cleared_after_the_last_wave() {
+        let level = level();
+        let mut waves = Waves::default();
+        let total = level.waves.iter().map(|wave| wave.count).sum::<u32>();
+        for _ in 0..total {
+            next_bug(&mut waves, &level, false).unwrap();
+        }
+        assert!(!waves.is_cleared());
+        assert_eq!(next_bug(&mut waves, &level, false), None);
+        assert!(waves.is_cleared());
+        assert_eq!(waves.wave, level.waves.len());
+
+        waves.restart();
+        assert_eq!(next_bug(&mut waves, &level, false).unwrap().0, 1);
+    }
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use bevy::{input::InputPlugin, prelude::*, time::TimeUpdateStrategy, window::WindowFocused};
+
+//
+// Run the game without window, rendering or audio output
+//
+// Only the asset storages and window events used by the game systems
+// are registered, the time advance a fixed step per frame so the
+// frames can be stepped with `App::update` in tests.
+//
+pub struct HeadlessPlugin {
+    pub frame_time: Duration,
+}
+
+impl Default for HeadlessPlugin {
+    fn default() -> Self {
+        Self {
+            frame_time: Duration::from_secs_f32(1. / 60.),
+        }
+    }
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            TransformPlugin,
+            HierarchyPlugin,
+            InputPlugin,
+        ))
+        .add_event::<WindowFocused>()
+        .add_asset::<Mesh>()
+        .add_asset::<StandardMaterial>()
+        .add_asset::<Image>()
+        .add_asset::<TextureAtlas>()
+        .add_asset::<Scene>()
+        .add_asset::<AnimationClip>()
+        .add_asset::<AudioSource>()
+        .add_asset::<Font>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(self.frame_time));
+    }
+}
@@ -1,5 +1,5 @@
 use bevy::{math::vec3, prelude::Vec3};
-use rand::{prelude::ThreadRng, Rng};
+use rand::Rng;
 
 // Explanation
 //
@@ -44,48 +44,33 @@ use rand::{prelude::ThreadRng, Rng};
 //     -20 <= y >= -12   ||   12 <= y >= 20
 //
 //
-pub fn generate_points(mut rnd: ThreadRng) -> Vec<Vec3> {
-    let mut points = Vec::new();
+pub const SCREEN_WIDTH: f32 = 20.;
+pub const SCREEN_HEIGHT: f32 = 12.;
 
-    // start point out of screen
-    points.push(vec3(
-        // Generate based on left/right margin
-        if rnd.gen_bool(0.5) {
-            rnd.gen_range(-25.0..=-20.)
-        } else {
-            rnd.gen_range(20.0..=25.)
-        },
-        // Generate based on top/bottom margin
-        if rnd.gen_bool(0.5) {
-            rnd.gen_range(-20.0..=-12.)
-        } else {
-            rnd.gen_range(12.0..=20.)
-        },
+/// Random point on the visible screen
+pub fn inside_screen(rnd: &mut (impl Rng + ?Sized)) -> Vec3 {
+    vec3(
+        rnd.gen_range(-SCREEN_WIDTH..=SCREEN_WIDTH),
+        rnd.gen_range(-SCREEN_HEIGHT..=SCREEN_HEIGHT),
         0.,
-    ));
-
-    for _ in 0..7 {
-        points.push(vec3(
-            rnd.gen_range(-20.0..=20.),
-            rnd.gen_range(-12.0..=12.),
-            0.,
-        ));
-    }
+    )
+}
 
-    // end point out of screen
-    points.push(vec3(
+/// Random point out of screen, for spawn and despawn the bugs
+pub fn out_of_screen(rnd: &mut (impl Rng + ?Sized)) -> Vec3 {
+    vec3(
+        // Generate based on left/right margin
         if rnd.gen_bool(0.5) {
             rnd.gen_range(-25.0..=-20.)
         } else {
             rnd.gen_range(20.0..=25.)
         },
+        // Generate based on top/bottom margin
         if rnd.gen_bool(0.5) {
             rnd.gen_range(-20.0..=-12.)
         } else {
             rnd.gen_range(12.0..=20.)
         },
         0.,
-    ));
-
-    points
+    )
 }
@@ -0,0 +1,444 @@
+use bevy::{
+    input::{touch::Touch, InputSystem},
+    prelude::*,
+    render::camera::RenderTarget,
+    ui::UiSystem,
+    utils::Uuid,
+    window::{PrimaryWindow, WindowRef},
+};
+use bevy_mod_picking::{
+    picking_core::{PickSet, PointerBundle},
+    pointer::{InputMove, InputPress, Location, PointerButton, PointerId},
+    prelude::*,
+};
+
+use crate::{
+    ui::{Palette, ThemeColor, ThemeRole},
+    GameState,
+};
+
+/// Viewport heights per second walked by the crosshair with the stick fully tilted
+const CROSSHAIR_SPEED: f32 = 0.8;
+/// Width and height in pixels of the crosshair
+const CROSSHAIR_SIZE: f32 = 32.;
+/// Virtual pointer of the crosshair, its presses are picked like the mouse ones
+const CROSSHAIR_POINTER: PointerId =
+    PointerId::Custom(Uuid::from_u128(0x6f1c_2d4a_8b3e_4f07_9a51_c2e8_d7b4_1a63));
+
+pub(crate) struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ActionEvent>()
+            // also added by the picking plugins, missing on the headless app
+            .add_event::<InputMove>()
+            .add_event::<InputPress>()
+            .add_event::<Pointer<Down>>()
+            .init_resource::<ActionBindings>()
+            .init_resource::<Crosshair>()
+            .add_systems(Startup, spawn_crosshair)
+            .add_systems(
+                PreUpdate,
+                (
+                    (keyboard_actions, pointer_actions, gamepad_actions)
+                        .after(InputSystem)
+                        .after(UiSystem::Focus)
+                        .in_set(PickSet::Input),
+                    picking_actions.in_set(PickSet::Last),
+                ),
+            )
+            .add_systems(
+                Update,
+                crosshair_print
+                    .run_if(resource_changed::<Crosshair>().or_else(state_changed::<GameState>())),
+            );
+    }
+}
+
+//
+// What the player wants to do, from any input device
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum GameAction {
+    /// Hit the bug under the pointer, the touch or the crosshair
+    Hit,
+    Pause,
+    Confirm,
+    Back,
+}
+
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ActionEvent {
+    pub action: GameAction,
+    /// Window position of the hits, in logical pixels
+    pub pos: Option<Vec2>,
+    /// Entity picked under the hit, with the world position on it
+    pub target: Option<(Entity, Option<Vec3>)>,
+}
+
+impl ActionEvent {
+    pub fn new(action: GameAction) -> Self {
+        Self {
+            action,
+            pos: None,
+            target: None,
+        }
+    }
+
+    pub fn at(mut self, pos: Vec2) -> Self {
+        self.pos = Some(pos);
+        self
+    }
+
+    pub fn on(mut self, entity: Entity, pos: Option<Vec3>) -> Self {
+        self.target = Some((entity, pos));
+        self
+    }
+}
+
+//
+// Inputs of every action, the touches always hit
+//
+#[derive(Resource, Clone, Debug)]
+pub(crate) struct ActionBindings {
+    pub keys: Vec<(KeyCode, GameAction)>,
+    pub mouse: Vec<(MouseButton, GameAction)>,
+    pub gamepad: Vec<(GamepadButtonType, GameAction)>,
+}
+
+impl Default for ActionBindings {
+    fn default() -> Self {
+        Self {
+            keys: vec![
+                (KeyCode::Escape, GameAction::Pause),
+                (KeyCode::P, GameAction::Pause),
+                (KeyCode::Return, GameAction::Confirm),
+                (KeyCode::Back, GameAction::Back),
+                // on the crosshair, aimed with the arrows
+                (KeyCode::Space, GameAction::Hit),
+            ],
+            mouse: vec![
+                (MouseButton::Left, GameAction::Hit),
+                (MouseButton::Right, GameAction::Back),
+            ],
+            gamepad: vec![
+                // hits while playing, confirms only on the overlays
+                (GamepadButtonType::South, GameAction::Hit),
+                (GamepadButtonType::South, GameAction::Confirm),
+                (GamepadButtonType::RightTrigger2, GameAction::Hit),
+                (GamepadButtonType::East, GameAction::Back),
+                (GamepadButtonType::Start, GameAction::Pause),
+            ],
+        }
+    }
+}
+
+//
+// Aim of the gamepads and the arrow keys, shown while playing
+// until the mouse or a touch is used again
+//
+#[derive(Resource, Default, Debug)]
+pub(crate) struct Crosshair {
+    /// Window position, the center until is moved
+    pub pos: Option<Vec2>,
+    pub active: bool,
+}
+
+impl Crosshair {
+    fn move_by(&mut self, delta: Vec2, size: Vec2) {
+        let pos = self.pos.unwrap_or(size / 2.) + delta;
+        self.pos = Some(pos.clamp(Vec2::ZERO, size));
+    }
+
+    /// Move with the direction of a stick, the arrows or the D-Pad
+    fn aim(&mut self, direction: Vec2, size: Vec2, delta_secs: f32) {
+        if direction == Vec2::ZERO {
+            return;
+        }
+        // the window Y goes down
+        let direction = direction.clamp_length_max(1.) * Vec2::new(1., -1.);
+        self.active = true;
+        self.move_by(direction * size.y * CROSSHAIR_SPEED * delta_secs, size);
+    }
+
+    /// Press of the crosshair pointer, the picking backend sends
+    /// the hit with what is under it
+    fn press(
+        &mut self,
+        window: Entity,
+        size: Vec2,
+        moves: &mut EventWriter<InputMove>,
+        presses: &mut EventWriter<InputPress>,
+    ) {
+        let pos = *self.pos.get_or_insert(size / 2.);
+        let Some(target) = RenderTarget::Window(WindowRef::Primary).normalize(Some(window)) else {
+            return;
+        };
+        self.active = true;
+        moves.send(InputMove::new(
+            CROSSHAIR_POINTER,
+            Location {
+                target,
+                position: pos,
+            },
+            Vec2::ZERO,
+        ));
+        presses.send(InputPress::new_down(
+            CROSSHAIR_POINTER,
+            PointerButton::Primary,
+        ));
+        presses.send(InputPress::new_up(
+            CROSSHAIR_POINTER,
+            PointerButton::Primary,
+        ));
+    }
+}
+
+#[derive(Component)]
+struct CrosshairUI;
+
+/// Window entity with its logical size
+fn window_size(window: &Query<(Entity, &Window), With<PrimaryWindow>>) -> Option<(Entity, Vec2)> {
+    let (entity, window) = window.get_single().ok()?;
+    Some((entity, Vec2::new(window.width(), window.height())))
+}
+
+// The hits are only sent while playing, a press on the pause
+// screen doesn't hit a bug once the game is resumed
+fn keyboard_actions(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    bindings: Res<ActionBindings>,
+    keys: Res<Input<KeyCode>>,
+    window: Query<(Entity, &Window), With<PrimaryWindow>>,
+    mut crosshair: ResMut<Crosshair>,
+    mut actions: EventWriter<ActionEvent>,
+    mut moves: EventWriter<InputMove>,
+    mut presses: EventWriter<InputPress>,
+) {
+    let playing = *state.get() == GameState::Game;
+    let window = window_size(&window).filter(|_| playing);
+    if let Some((_, size)) = window {
+        let mut direction = Vec2::ZERO;
+        for (key, step) in [
+            (KeyCode::Left, Vec2::NEG_X),
+            (KeyCode::Right, Vec2::X),
+            (KeyCode::Up, Vec2::Y),
+            (KeyCode::Down, Vec2::NEG_Y),
+        ] {
+            if keys.pressed(key) {
+                direction += step;
+            }
+        }
+        crosshair.aim(direction, size, time.delta_seconds());
+    }
+
+    for (key, action) in bindings.keys.iter() {
+        if !keys.just_pressed(*key) {
+            continue;
+        }
+        match (*action, window) {
+            (GameAction::Hit, Some((entity, size))) => {
+                crosshair.press(entity, size, &mut moves, &mut presses)
+            }
+            (GameAction::Hit, None) => {}
+            _ => actions.send(ActionEvent::new(*action)),
+        }
+    }
+}
+
+fn pointer_actions(
+    bindings: Res<ActionBindings>,
+    mouse: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    mut cursor: EventReader<CursorMoved>,
+    mut crosshair: ResMut<Crosshair>,
+    mut actions: EventWriter<ActionEvent>,
+) {
+    let tapped = touches.iter_just_pressed().next().is_some();
+    if crosshair.active && (cursor.iter().last().is_some() || tapped) {
+        crosshair.active = false;
+    }
+    // the browsers repeat the taps as clicks
+    if tapped {
+        return;
+    }
+    // the hits come from the picking, with the bug under the pointer
+    for (button, action) in bindings.mouse.iter() {
+        if *action != GameAction::Hit && mouse.just_pressed(*button) {
+            actions.send(ActionEvent::new(*action));
+        }
+    }
+}
+
+fn gamepad_actions(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    bindings: Res<ActionBindings>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    window: Query<(Entity, &Window), With<PrimaryWindow>>,
+    mut crosshair: ResMut<Crosshair>,
+    mut actions: EventWriter<ActionEvent>,
+    mut moves: EventWriter<InputMove>,
+    mut presses: EventWriter<InputPress>,
+) {
+    let playing = *state.get() == GameState::Game;
+    let window = window_size(&window).filter(|_| playing);
+
+    for gamepad in gamepads.iter() {
+        let axis = |kind| axes.get(GamepadAxis::new(gamepad, kind)).unwrap_or(0.);
+        let pressed = |kind| buttons.pressed(GamepadButton::new(gamepad, kind));
+        let mut direction = Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        for (kind, step) in [
+            (GamepadButtonType::DPadLeft, Vec2::NEG_X),
+            (GamepadButtonType::DPadRight, Vec2::X),
+            (GamepadButtonType::DPadUp, Vec2::Y),
+            (GamepadButtonType::DPadDown, Vec2::NEG_Y),
+        ] {
+            if pressed(kind) {
+                direction += step;
+            }
+        }
+        if let Some((_, size)) = window {
+            crosshair.aim(direction, size, time.delta_seconds());
+        }
+
+        for (button, action) in bindings.gamepad.iter() {
+            if !buttons.just_pressed(GamepadButton::new(gamepad, *button)) {
+                continue;
+            }
+            match (action, window) {
+                // South also hits, so it only confirms on the overlays
+                (GameAction::Confirm, _) if playing => {}
+                (GameAction::Hit, Some((entity, size))) => {
+                    crosshair.press(entity, size, &mut moves, &mut presses)
+                }
+                (GameAction::Hit, None) => {}
+                _ => actions.send(ActionEvent::new(*action)),
+            }
+        }
+    }
+}
+
+//
+// Presses picked on the bugs or the background, from the mouse,
+// every finger and the crosshair, as hits while playing
+//
+fn picking_actions(
+    state: Res<State<GameState>>,
+    bindings: Res<ActionBindings>,
+    interactions: Query<&Interaction>,
+    mut downs: EventReader<Pointer<Down>>,
+    mut actions: EventWriter<ActionEvent>,
+) {
+    let downs = downs.iter().collect::<Vec<_>>();
+    // the buttons of the overlays take the press
+    let over_ui = interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+    if *state.get() != GameState::Game || over_ui {
+        return;
+    }
+    // the browsers repeat the taps as clicks
+    let tapped = downs
+        .iter()
+        .any(|down| matches!(down.pointer_id, PointerId::Touch(_)));
+    let mouse_hits = |pressed: PointerButton| {
+        bindings.mouse.iter().any(|(button, action)| {
+            *action == GameAction::Hit
+                && match button {
+                    MouseButton::Left => pressed == PointerButton::Primary,
+                    MouseButton::Right => pressed == PointerButton::Secondary,
+                    MouseButton::Middle => pressed == PointerButton::Middle,
+                    MouseButton::Other(_) => false,
+                }
+        })
+    };
+    for down in downs {
+        if down.pointer_id == PointerId::Mouse && (tapped || !mouse_hits(down.button)) {
+            continue;
+        }
+        actions.send(
+            ActionEvent::new(GameAction::Hit)
+                .at(down.pointer_location.position)
+                .on(down.target, down.hit.position),
+        );
+    }
+}
+
+fn spawn_crosshair(mut cmd: Commands) {
+    cmd.spawn(PointerBundle::new(CROSSHAIR_POINTER));
+    cmd.spawn((
+        NodeBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                width: Val::Px(CROSSHAIR_SIZE),
+                height: Val::Px(CROSSHAIR_SIZE),
+                border: UiRect::all(Val::Px(3.)),
+                ..default()
+            },
+            border_color: BorderColor(Palette::DEFAULT.accent),
+            // under the pause and game over screens
+            z_index: ZIndex::Global(5),
+            ..default()
+        },
+        ThemeColor::new(ThemeRole::Accent),
+        CrosshairUI,
+    ));
+}
+
+fn crosshair_print(
+    crosshair: Res<Crosshair>,
+    state: Res<State<GameState>>,
+    mut ui: Query<&mut Style, With<CrosshairUI>>,
+) {
+    let pos = crosshair
+        .pos
+        .filter(|_| crosshair.active && *state.get() == GameState::Game);
+    for mut style in ui.iter_mut() {
+        let Some(pos) = pos else {
+            style.display = Display::None;
+            continue;
+        };
+        style.display = Display::Flex;
+        style.left = Val::Px(pos.x - CROSSHAIR_SIZE / 2.);
+        style.top = Val::Px(pos.y - CROSSHAIR_SIZE / 2.);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crosshair_starts_centered_and_stays_inside() {
+        let size = Vec2::new(800., 600.);
+        let mut crosshair = Crosshair::default();
+        crosshair.move_by(Vec2::new(10., -10.), size);
+        assert_eq!(crosshair.pos, Some(Vec2::new(410., 290.)));
+        crosshair.move_by(Vec2::new(1000., 1000.), size);
+        assert_eq!(crosshair.pos, Some(size));
+        crosshair.move_by(Vec2::splat(-2000.), size);
+        assert_eq!(crosshair.pos, Some(Vec2::ZERO));
+    }
+
+    #[test]
+    fn aim_goes_up_with_the_window_y_down() {
+        let size = Vec2::new(800., 600.);
+        let mut crosshair = Crosshair::default();
+        crosshair.aim(Vec2::ZERO, size, 1.);
+        assert!(!crosshair.active);
+        crosshair.aim(Vec2::new(0., 2.), size, 0.5);
+        assert!(crosshair.active);
+        assert_eq!(
+            crosshair.pos,
+            Some(Vec2::new(400., 300. - 600. * CROSSHAIR_SPEED / 2.))
+        );
+    }
+}
@@ -1,33 +1,25 @@
 use bevy::prelude::*;
 use bevy_tweening::lens::*;
 
-pub trait InstanceLens {
-    fn create(start: Color, end: Color) -> Self;
-}
+use crate::ui::ThemeColor;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct GameTextColorLens {
-    /// Start color.
-    pub start: Color,
-    /// End color.
-    pub end: Color,
+pub struct ThemeAlphaLens {
+    /// Start alpha, as fraction of the palette one.
+    pub start: f32,
+    /// End alpha, as fraction of the palette one.
+    pub end: f32,
 }
 
-impl InstanceLens for GameTextColorLens {
-    fn create(start: Color, end: Color) -> Self {
+impl ThemeAlphaLens {
+    pub fn create(start: f32, end: f32) -> Self {
         Self { start, end }
     }
 }
 
-impl Lens<Text> for GameTextColorLens {
-    fn lerp(&mut self, target: &mut Text, ratio: f32) {
-        let start: Vec4 = self.start.into();
-        let end: Vec4 = self.end.into();
-        let value = start.lerp(end, ratio);
-        target
-            .sections
-            .iter_mut()
-            .for_each(|section| section.style.color = value.into());
+impl Lens<ThemeColor> for ThemeAlphaLens {
+    fn lerp(&mut self, target: &mut ThemeColor, ratio: f32) {
+        target.alpha = self.start + (self.end - self.start) * ratio;
     }
 }
 
@@ -9,40 +9,46 @@ use bevy_sprite3d::Sprite3dPlugin;
 use bevy_tweening::TweeningPlugin;
 use effects::EffectsPlugin;
 use game::Game;
-use lazy_static::lazy_static;
 
-use locale::LocalePlugin;
+use audio::AudioPlugin;
+use config::ConfigPlugin;
+use input::ActionsPlugin;
+use locale::{Locale, LocalePlugin};
+use particles::ParticlesPlugin;
+use rng::RngPlugin;
+use settings::SettingsPlugin;
+use stats::StatsPlugin;
+use storage::StorageResource;
 use ui::UiPlugin;
 
+mod audio;
+mod config;
+mod data;
 mod effects;
-mod ext;
 mod game;
+mod headless;
 mod helper;
+mod input;
 mod lens;
 mod locale;
+mod particles;
+mod path;
+mod rng;
+mod settings;
+mod stats;
+mod storage;
 mod ui;
 
-pub use locale::LocaleLangs;
+pub use config::{Difficulty, GameConfig, GameMode};
+pub use headless::HeadlessPlugin;
+pub use locale::LanguageTag;
+pub use rng::GameRng;
+pub use storage::{FileStorage, MemoryStorage, Storage};
 
 //
 // Launcher Game Enviroment Variables
 //
 pub const LAUNCHER_TITLE: &str = "Kill Errors";
-pub(crate) const HOME_URL: &str = env!("HOME_URL");
-
-//
-// Game Enviroment Variables
-//
-lazy_static! {
-    pub(crate) static ref TIME_WAIT_TO_START: u64 = {
-        let time = env!("TIME_WAIT_TO_START");
-        time.parse::<u64>().unwrap_or(10)
-    };
-    pub(crate) static ref MAX_BUGS_ON_SCREEN: usize = {
-        let time = env!("MAX_BUGS_ON_SCREEN");
-        time.parse::<usize>().unwrap_or(30)
-    };
-}
 
 #[derive(Resource)]
 pub(crate) struct OpenLinkResource(pub Box<dyn Fn(&str) + Sync + Send + 'static>);
@@ -52,11 +58,15 @@ pub(crate) enum GameState {
     #[default]
     MainPage,
     Game,
+    Paused,
+    GameOver,
 }
 
 pub fn app(
     fullscreen: bool,
-    lang: LocaleLangs,
+    lang: &str,
+    config: GameConfig,
+    storage: impl Storage,
     open_url: impl Fn(&str) + Sync + Send + 'static,
 ) -> App {
     let mode = if fullscreen {
@@ -65,36 +75,80 @@ pub fn app(
         WindowMode::Windowed
     };
 
+    let plugins = DefaultPlugins
+        .set(WindowPlugin {
+            primary_window: Some(Window {
+                mode,
+                title: LAUNCHER_TITLE.to_string(),
+                fit_canvas_to_parent: true,
+                prevent_default_event_handling: true,
+                present_mode: bevy::window::PresentMode::AutoVsync,
+                decorations: false,
+                ..default()
+            }),
+            ..default()
+        })
+        .disable::<LogPlugin>();
+    // Hot reload of the assets while developing, like the `.locale` files,
+    // bevy panics when the watcher is asked on wasm or android
+    #[cfg(all(
+        feature = "inspect",
+        not(target_family = "wasm"),
+        not(target_os = "android")
+    ))]
+    let plugins = plugins.set(AssetPlugin {
+        watch_for_changes: bevy::asset::ChangeWatcher::with_delay(
+            std::time::Duration::from_millis(200),
+        ),
+        ..default()
+    });
+
     let mut app = App::new();
     app.insert_resource(OpenLinkResource(Box::new(open_url)))
-        .add_plugins(
-            DefaultPlugins
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        mode,
-                        title: LAUNCHER_TITLE.to_string(),
-                        fit_canvas_to_parent: true,
-                        prevent_default_event_handling: true,
-                        present_mode: bevy::window::PresentMode::AutoVsync,
-                        decorations: false,
-                        ..default()
-                    }),
-                    ..default()
-                })
-                .disable::<LogPlugin>(),
-        )
-        .add_plugins(DefaultPickingPlugins.build())
-        .add_plugins((Sprite3dPlugin, TweeningPlugin));
+        .insert_resource(StorageResource(Box::new(storage)))
+        .add_plugins(plugins)
+        // the hits of the input actions are picked on the bug meshes
+        .add_plugins(DefaultPickingPlugins.build());
     #[cfg(feature = "inspect")]
     app.add_plugins(WorldInspectorPlugin::new());
-    app.add_state::<GameState>()
-        .insert_resource(lang)
-        .add_systems(Startup, setup_camera)
-        .add_plugins((LocalePlugin, UiPlugin, EffectsPlugin, Game));
+    app.add_systems(Startup, setup_camera);
+    add_game(&mut app, lang, config);
 
     app
 }
 
+/// Same game of [`app`] without window, rendering or audio output,
+/// frames are stepped manually with [`App::update`]
+pub fn app_headless(lang: &str, config: GameConfig) -> App {
+    let mut app = App::new();
+    app.insert_resource(OpenLinkResource(Box::new(|_| {})))
+        .insert_resource(StorageResource(Box::<MemoryStorage>::default()))
+        .add_plugins(HeadlessPlugin::default());
+    add_game(&mut app, lang, config);
+
+    app
+}
+
+fn add_game(app: &mut App, lang: &str, config: GameConfig) {
+    app.insert_resource(config)
+        .add_plugins((Sprite3dPlugin, TweeningPlugin))
+        .add_state::<GameState>()
+        .insert_resource(Locale::new(lang))
+        .add_plugins((
+            ConfigPlugin,
+            RngPlugin,
+            LocalePlugin,
+            SettingsPlugin,
+            ActionsPlugin,
+            AudioPlugin,
+            UiPlugin,
+            EffectsPlugin,
+            ParticlesPlugin,
+            Game,
+            StatsPlugin,
+        ));
+}
+
 fn setup_camera(mut cmd: Commands) {
     cmd.spawn((
         Camera3dBundle {
@@ -1,37 +1,279 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*, reflect::TypeUuid};
+use serde::Deserialize;
 
+#[cfg(feature = "inspect")]
+use bevy::time::common_conditions::on_timer;
 #[cfg(feature = "inspect")]
 use bevy_inspector_egui::prelude::*;
+#[cfg(feature = "inspect")]
+use std::time::Duration;
+
+use crate::{
+    config::GameConfig,
+    data::{RonAsset, RonAssetAppExt},
+};
 
 use self::asset::LocaleAssetLoader;
 
 mod asset;
+mod parser;
+mod pattern;
+#[cfg(feature = "inspect")]
+mod report;
+mod tag;
 
 pub use self::asset::LocaleAsset;
+pub use self::pattern::LocaleArgs;
+pub use self::tag::{negotiate, LanguageTag};
 
 pub struct LocalePlugin;
 
 impl Plugin for LocalePlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<LocaleLangs>()
+        app.register_type::<Locale>()
+            .add_event::<LocaleChanged>()
             .add_asset::<LocaleAsset>()
-            .init_asset_loader::<LocaleAssetLoader>();
+            .init_asset_loader::<LocaleAssetLoader>()
+            .add_ron_asset::<LocaleManifest>()
+            .add_systems(
+                Startup,
+                |asset_server: Res<AssetServer>,
+                 config: Res<GameConfig>,
+                 mut locale: ResMut<Locale>| {
+                    locale.manifest = asset_server.load(config.locales.as_str());
+                },
+            )
+            .add_systems(PreUpdate, (load_languages, change_language).chain())
+            .add_systems(Update, update_localized_text);
+
+        #[cfg(feature = "inspect")]
+        app.register_type::<report::MissingLocaleKeys>()
+            .init_resource::<report::MissingLocaleKeys>()
+            .add_systems(
+                Update,
+                report::report_missing_keys.run_if(on_timer(Duration::from_secs(5))),
+            );
     }
 }
 
-#[derive(Clone, Copy, Debug, Resource, Reflect)]
+//
+// Languages available on the assets, the first is used
+// when the requested language is not found
+//
+#[derive(Debug, Deserialize, Reflect, TypeUuid)]
+#[uuid = "4a1f9d53-6c2e-4b7e-8d0a-2f5c3e7b9a14"]
+pub struct LocaleManifest {
+    pub languages: Vec<LocaleEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Reflect)]
+pub struct LocaleEntry {
+    pub tag: LanguageTag,
+    /// Name of the language on itself, like `Español`
+    pub name: String,
+    /// Path of the `.locale` file
+    pub file: String,
+}
+
+impl RonAsset for LocaleManifest {
+    const EXTENSIONS: &'static [&'static str] = &["locales.ron"];
+}
+
+//
+// Requested language and the dictionaries negotiated for it
+//
+#[derive(Debug, Default, Resource, Reflect)]
 #[cfg_attr(feature = "inspect", derive(InspectorOptions))]
 #[cfg_attr(feature = "inspect", reflect(InspectorOptions))]
-pub enum LocaleLangs {
-    EN = 0,
-    ES = 1,
+pub struct Locale {
+    pub requested: LanguageTag,
+    /// From the best match to the default language
+    pub chain: Vec<LanguageTag>,
+    #[reflect(ignore)]
+    handles: Vec<Handle<LocaleAsset>>,
+    #[reflect(ignore)]
+    manifest: Handle<LocaleManifest>,
 }
 
-impl LocaleLangs {
-    pub fn to_name(&self) -> &str {
-        match self {
-            LocaleLangs::EN => "en-EN",
-            LocaleLangs::ES => "es-ES",
+impl Locale {
+    pub fn new(requested: impl Into<LanguageTag>) -> Self {
+        Self {
+            requested: requested.into(),
+            ..default()
         }
     }
+
+    /// Language used to show the texts
+    pub fn active(&self) -> Option<&LanguageTag> {
+        self.chain.first()
+    }
+
+    fn resolve(&mut self, manifest: &LocaleManifest, asset_server: &AssetServer) {
+        let Some(default) = manifest.languages.first() else {
+            log::error!("The locale manifest has no languages");
+            return;
+        };
+        let available = manifest
+            .languages
+            .iter()
+            .map(|entry| entry.tag.clone())
+            .collect::<Vec<_>>();
+        self.chain = negotiate(&self.requested, &available, &default.tag);
+        self.handles = self
+            .chain
+            .iter()
+            .filter_map(|tag| manifest.languages.iter().find(|entry| entry.tag == *tag))
+            .map(|entry| asset_server.load(entry.file.as_str()))
+            .collect();
+        log::info!("Language {} resolved as {:?}", self.requested, self.chain);
+    }
+}
+
+/// Request to change the language, the [`LocalizedText`] are updated once is loaded
+#[derive(Event, Clone, Debug)]
+pub struct LocaleChanged(pub LanguageTag);
+
+//
+// Text section resolved from a message of the active language
+//
+#[derive(Component, Clone, Debug)]
+pub struct LocalizedText {
+    pub key: String,
+    pub section: usize,
+    pub args: LocaleArgs,
+}
+
+impl LocalizedText {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            section: 0,
+            args: LocaleArgs::new(),
+        }
+    }
+
+    pub fn with_section(mut self, section: usize) -> Self {
+        self.section = section;
+        self
+    }
+
+    pub fn with_args(mut self, args: LocaleArgs) -> Self {
+        self.args = args;
+        self
+    }
+}
+
+fn load_languages(
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<LocaleManifest>>,
+    mut events: EventReader<AssetEvent<LocaleManifest>>,
+    mut locale: ResMut<Locale>,
+) {
+    for event in events.iter() {
+        let (AssetEvent::Created { handle } | AssetEvent::Modified { handle }) = event else {
+            continue;
+        };
+        if *handle != locale.manifest {
+            continue;
+        }
+        let Some(manifest) = manifests.get(handle) else { continue; };
+        locale.resolve(manifest, &asset_server);
+    }
+}
+
+fn change_language(
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<LocaleManifest>>,
+    mut events: EventReader<LocaleChanged>,
+    mut locale: ResMut<Locale>,
+) {
+    let Some(LocaleChanged(tag)) = events.iter().last() else { return; };
+    locale.requested = tag.clone();
+    if let Some(manifest) = manifests.get(&locale.manifest) {
+        locale.resolve(manifest, &asset_server);
+    }
+}
+
+fn update_localized_text(
+    localizer: Localizer,
+    mut events: EventReader<AssetEvent<LocaleAsset>>,
+    mut texts: Query<(Ref<LocalizedText>, &mut Text)>,
+) {
+    let assets_changed = events.iter().count() > 0;
+    let reload = assets_changed || localizer.locale.is_changed();
+    let Some(bundle) = localizer.bundle() else { return; };
+    for (localized, mut text) in texts.iter_mut() {
+        if !reload && !localized.is_changed() {
+            continue;
+        }
+        let Some(section) = text.sections.get_mut(localized.section) else { continue; };
+        section.value = bundle
+            .format(&localized.key, &localized.args)
+            .unwrap_or_else(|| localized.key.clone());
+    }
+}
+
+//
+// Access to the messages of the active language
+//
+#[derive(SystemParam)]
+pub struct Localizer<'w> {
+    locale: Res<'w, Locale>,
+    assets: Res<'w, Assets<LocaleAsset>>,
+    manifests: Res<'w, Assets<LocaleManifest>>,
+}
+
+impl<'w> Localizer<'w> {
+    pub fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    /// Languages on the manifest, empty until is loaded
+    pub fn languages(&self) -> &[LocaleEntry] {
+        self.manifests
+            .get(&self.locale.manifest)
+            .map_or(&[], |manifest| manifest.languages.as_slice())
+    }
+
+    /// Dictionaries of the language and its fallbacks, none until the language is loaded
+    pub fn bundle(&self) -> Option<LocaleBundle<'_>> {
+        self.assets.get(self.locale.handles.first()?)?;
+        let chain = self
+            .locale
+            .handles
+            .iter()
+            .filter_map(|handle| self.assets.get(handle))
+            .collect();
+        Some(LocaleBundle::new(chain))
+    }
+}
+
+//
+// Chain of dictionaries, a message missing on a language
+// is taken from the next one
+//
+pub struct LocaleBundle<'a> {
+    chain: Vec<&'a LocaleAsset>,
+}
+
+impl<'a> LocaleBundle<'a> {
+    pub fn new(chain: Vec<&'a LocaleAsset>) -> Self {
+        Self { chain }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        #[cfg(feature = "inspect")]
+        report::record_key(key);
+        self.chain.iter().find_map(|asset| asset.get(key))
+    }
+
+    pub fn get_default(&self, key: &str, def: &'a str) -> &'a str {
+        self.get(key).unwrap_or(def)
+    }
+
+    pub fn format(&self, key: &str, args: &LocaleArgs) -> Option<String> {
+        #[cfg(feature = "inspect")]
+        report::record_key(key);
+        self.chain.iter().find_map(|asset| asset.format(key, args))
+    }
 }
@@ -8,23 +8,41 @@ use bevy::{
 #[cfg(feature = "inspect")]
 use bevy_inspector_egui::prelude::*;
 
+use super::{
+    parser::parse_locale,
+    pattern::{LocaleArgs, Pattern},
+    tag::LanguageTag,
+};
+
 #[derive(Debug, Resource, Default, Reflect, TypeUuid)]
 #[cfg_attr(feature = "inspect", derive(InspectorOptions))]
 #[cfg_attr(feature = "inspect", reflect(InspectorOptions))]
 #[uuid = "39cadc56-aa9c-4543-8640-a018b74b5082"]
 pub struct LocaleAsset {
+    /// Language tag, from the file name
+    pub lang: LanguageTag,
     pub value: HashMap<String, String>,
+    #[reflect(ignore)]
+    patterns: HashMap<String, Pattern>,
 }
 
 impl LocaleAsset {
-    #[allow(unused)]
     pub fn get(&self, key: &str) -> Option<&str> {
         self.value.get(key).map(|v| v.as_str())
     }
 
     pub fn get_default<'a>(&'a self, key: &str, def: &'a str) -> &str {
+        #[cfg(feature = "inspect")]
+        super::report::record_key(key);
         self.get(key).unwrap_or(def)
     }
+
+    /// Message with the placeholders and plurals resolved
+    pub fn format(&self, key: &str, args: &LocaleArgs) -> Option<String> {
+        self.patterns
+            .get(key)
+            .map(|pattern| pattern.format(self.lang.as_str(), args))
+    }
 }
 
 #[derive(Default)]
@@ -37,18 +55,23 @@ impl AssetLoader for LocaleAssetLoader {
         load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
         Box::pin(async move {
-            let mut asset = LocaleAsset::default();
-            let content = String::from_utf8(bytes.to_vec())?;
-            log::info!("File Readed");
-            asset.value.clear();
-            for line in content.lines() {
-                if !line.starts_with('#') {
-                    let (key, value) = line.split_once('=').unwrap();
-                    asset
-                        .value
-                        .insert(key.trim().to_string(), value.trim().to_string());
-                }
+            let content = std::str::from_utf8(bytes)?;
+            let mut asset = LocaleAsset {
+                value: parse_locale(content)?,
+                ..default()
+            };
+            for (key, value) in asset.value.iter() {
+                let pattern = Pattern::parse(value).unwrap_or_else(|e| {
+                    log::warn!("Invalid message `{key}`: {e}");
+                    Pattern::text(value)
+                });
+                asset.patterns.insert(key.clone(), pattern);
             }
+            asset.lang = load_context
+                .path()
+                .file_stem()
+                .map(|stem| LanguageTag::new(&stem.to_string_lossy()))
+                .unwrap_or_default();
             load_context.set_default_asset(LoadedAsset::new(asset));
             Ok(())
         })
@@ -0,0 +1,310 @@
+use std::fmt;
+
+use bevy::utils::HashMap;
+
+//
+// Parser of the `.locale` files
+//
+//   # comment
+//   key = value with \n escapes
+//   long = first part \
+//          joined without line break
+//   multi = first line
+//       second line, indented lines are joined with a line break
+//
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocaleError {
+    /// Line without `=` between the key and the value
+    MissingSeparator {
+        line: usize,
+    },
+    EmptyKey {
+        line: usize,
+    },
+    DuplicateKey {
+        line: usize,
+        key: String,
+    },
+    InvalidEscape {
+        line: usize,
+        escape: String,
+    },
+    /// Indented or continued line without a previous message
+    UnexpectedContinuation {
+        line: usize,
+    },
+}
+
+impl fmt::Display for LocaleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocaleError::MissingSeparator { line } => {
+                write!(f, "line {line}: expected `key = value`")
+            }
+            LocaleError::EmptyKey { line } => write!(f, "line {line}: the key is empty"),
+            LocaleError::DuplicateKey { line, key } => {
+                write!(f, "line {line}: the key `{key}` is already defined")
+            }
+            LocaleError::InvalidEscape { line, escape } => {
+                write!(f, "line {line}: unknown escape `\\{escape}`")
+            }
+            LocaleError::UnexpectedContinuation { line } => {
+                write!(f, "line {line}: continuation without a previous message")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LocaleError {}
+
+pub fn parse_locale(content: &str) -> Result<HashMap<String, String>, LocaleError> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut values = HashMap::new();
+    // key of the message which receives the continuation lines
+    let mut current: Option<String> = None;
+    // the previous line ended with `\`
+    let mut joined = false;
+
+    for (i, raw) in content.lines().enumerate() {
+        let line = i + 1;
+        let indented = raw.starts_with([' ', '\t']);
+        let trimmed = raw.trim();
+
+        if joined || (indented && !trimmed.is_empty() && !trimmed.starts_with('#')) {
+            let Some(key) = current.as_ref() else {
+                return Err(LocaleError::UnexpectedContinuation { line });
+            };
+            let (text, continues) = unescape(trim_value(raw), line)?;
+            let value: &mut String = values.get_mut(key).expect("current key is inserted");
+            if !joined {
+                value.push('\n');
+            }
+            value.push_str(&text);
+            joined = continues;
+            continue;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            current = None;
+            continue;
+        }
+
+        let Some((key, value)) = raw.split_once('=') else {
+            return Err(LocaleError::MissingSeparator { line });
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(LocaleError::EmptyKey { line });
+        }
+        if values.contains_key(key) {
+            return Err(LocaleError::DuplicateKey {
+                line,
+                key: key.to_string(),
+            });
+        }
+        let (value, continues) = unescape(trim_value(value), line)?;
+        values.insert(key.to_string(), value);
+        current = Some(key.to_string());
+        joined = continues;
+    }
+    Ok(values)
+}
+
+/// Trim the spaces around the value, but not the escaped one of a trailing `\ `
+fn trim_value(text: &str) -> &str {
+    let text = text.trim_start();
+    let end = text.trim_end().len();
+    let backslashes = text[..end].chars().rev().take_while(|c| *c == '\\').count();
+    if backslashes % 2 == 0 {
+        return &text[..end];
+    }
+    let escaped = text[end..].chars().next().map_or(0, char::len_utf8);
+    &text[..end + escaped]
+}
+
+/// Resolve the escapes, returns if the line ends with `\` to continue on the next one
+fn unescape(text: &str, line: usize) -> Result<(String, bool), LocaleError> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            None => return Ok((out, true)),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('\\') => out.push('\\'),
+            Some('#') => out.push('#'),
+            Some('=') => out.push('='),
+            Some(' ') => out.push(' '),
+            Some('u') => {
+                let code = chars.by_ref().take(4).collect::<String>();
+                let Some(c) = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) else {
+                    return Err(LocaleError::InvalidEscape {
+                        line,
+                        escape: format!("u{code}"),
+                    });
+                };
+                out.push(c);
+            }
+            Some(other) => {
+                return Err(LocaleError::InvalidEscape {
+                    line,
+                    escape: other.to_string(),
+                })
+            }
+        }
+    }
+    Ok((out, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> HashMap<String, String> {
+        parse_locale(content).unwrap()
+    }
+
+    #[test]
+    fn key_values() {
+        let values = parse("button=Back to Home\nmessage = Patience  \n");
+        assert_eq!(values["button"], "Back to Home");
+        assert_eq!(values["message"], "Patience");
+        assert_eq!(values.len(), 2);
+    }
+
+    #[test]
+    fn blank_lines_and_comments() {
+        let values = parse("\n# title\n\n   \nbest=Best\n\t# indented comment\n");
+        assert_eq!(values.len(), 1);
+        assert_eq!(values["best"], "Best");
+    }
+
+    #[test]
+    fn separator_inside_value() {
+        assert_eq!(parse("equation=1 + 1 = 2")["equation"], "1 + 1 = 2");
+    }
+
+    #[test]
+    fn bom_and_crlf() {
+        let values = parse("\u{feff}first=One\r\nsecond=Two\r\n");
+        assert_eq!(values["first"], "One");
+        assert_eq!(values["second"], "Two");
+    }
+
+    #[test]
+    fn escapes() {
+        let values = parse(r"text=\ Line\nTab\tSlash\\ \#hash \= \u00e9");
+        assert_eq!(values["text"], " Line\nTab\tSlash\\ #hash = é");
+    }
+
+    #[test]
+    fn trailing_escaped_space() {
+        let values = parse("space=foo\\ \nslash=foo\\\\  \nnext=value");
+        assert_eq!(values["space"], "foo ");
+        assert_eq!(values["slash"], "foo\\");
+        assert_eq!(values["next"], "value");
+        let values = parse("long=first \\\n    second\\ \t\nnext=value");
+        assert_eq!(values["long"], "first second ");
+    }
+
+    #[test]
+    fn backslash_joins_lines() {
+        let values = parse("long=first part \\\n    second part\nnext=value");
+        assert_eq!(values["long"], "first part second part");
+        assert_eq!(values["next"], "value");
+    }
+
+    #[test]
+    fn indented_lines_continue_the_value() {
+        let values = parse("multi=first line\n    second line\n\tthird line\nnext=value");
+        assert_eq!(values["multi"], "first line\nsecond line\nthird line");
+        assert_eq!(values["next"], "value");
+    }
+
+    #[test]
+    fn multiline_select() {
+        let values =
+            parse("kills={ $count ->\n    [one] One bug\n   *[other] { $count } bugs\n  }\n");
+        assert_eq!(
+            values["kills"],
+            "{ $count ->\n[one] One bug\n*[other] { $count } bugs\n}"
+        );
+    }
+
+    #[test]
+    fn missing_separator() {
+        assert_eq!(
+            parse_locale("ok=yes\nno separator"),
+            Err(LocaleError::MissingSeparator { line: 2 })
+        );
+    }
+
+    #[test]
+    fn empty_key() {
+        assert_eq!(
+            parse_locale("# comment\n= value"),
+            Err(LocaleError::EmptyKey { line: 2 })
+        );
+    }
+
+    #[test]
+    fn duplicate_key() {
+        assert_eq!(
+            parse_locale("best=Best\nbest=Mejor"),
+            Err(LocaleError::DuplicateKey {
+                line: 2,
+                key: "best".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_escapes() {
+        assert_eq!(
+            parse_locale("a=b\nbad=\\q"),
+            Err(LocaleError::InvalidEscape {
+                line: 2,
+                escape: "q".to_string()
+            })
+        );
+        assert_eq!(
+            parse_locale("bad=\\uzz"),
+            Err(LocaleError::InvalidEscape {
+                line: 1,
+                escape: "uzz".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn continuation_without_message() {
+        assert_eq!(
+            parse_locale("  indented"),
+            Err(LocaleError::UnexpectedContinuation { line: 1 })
+        );
+        assert_eq!(
+            parse_locale("a=b\n\n  indented"),
+            Err(LocaleError::UnexpectedContinuation { line: 3 })
+        );
+    }
+
+    #[test]
+    fn error_messages_have_line() {
+        let error = parse_locale("a=b\nc").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: expected `key = value`");
+    }
+
+    #[test]
+    fn game_locales_are_valid() {
+        for content in [
+            include_str!("../../assets/locale/en-EN.locale"),
+            include_str!("../../assets/locale/es-ES.locale"),
+        ] {
+            let values = parse(content);
+            assert!(values.contains_key("button"));
+        }
+    }
+}
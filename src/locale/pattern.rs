@@ -0,0 +1,364 @@
+use std::fmt;
+
+use bevy::utils::HashMap;
+
+//
+// Localized message, a Fluent like syntax in a single value:
+//
+//   score=Score: { $score }
+//   kills={ $count -> [0] No bugs [one] One bug *[other] { $count } bugs }
+//
+// The variant marked with `*` is used when no other variant matches,
+// the keys are exact numbers, like `[1.5]`, or plural categories of the language.
+//
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern(Vec<Element>);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    Text(String),
+    Var(String),
+    Select {
+        var: String,
+        variants: Vec<(VariantKey, Pattern)>,
+        default: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum VariantKey {
+    Number(f64),
+    Category(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LocaleValue {
+    Number(f64),
+    Text(String),
+}
+
+impl fmt::Display for LocaleValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocaleValue::Number(n) if n.fract() == 0. => write!(f, "{n:.0}"),
+            LocaleValue::Number(n) => write!(f, "{n}"),
+            LocaleValue::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+macro_rules! number_value {
+    ($($t:ty),*) => {
+        $(impl From<$t> for LocaleValue {
+            fn from(value: $t) -> Self {
+                LocaleValue::Number(value as f64)
+            }
+        })*
+    };
+}
+
+number_value!(u32, u64, usize, i32, i64, f32, f64);
+
+impl From<&str> for LocaleValue {
+    fn from(value: &str) -> Self {
+        LocaleValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for LocaleValue {
+    fn from(value: String) -> Self {
+        LocaleValue::Text(value)
+    }
+}
+
+/// Values for the `{ $name }` placeholders of a [`Pattern`]
+#[derive(Debug, Clone, Default)]
+pub struct LocaleArgs(HashMap<String, LocaleValue>);
+
+impl LocaleArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: &str, value: impl Into<LocaleValue>) -> Self {
+        self.0.insert(name.to_string(), value.into());
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&LocaleValue> {
+        self.0.get(name)
+    }
+}
+
+/// CLDR plural category of a number, for the languages of the game
+pub fn plural_category(lang: &str, n: f64) -> &'static str {
+    let lang = lang.split(['-', '_']).next().unwrap_or_default();
+    let integer = n.fract() == 0.;
+    match lang {
+        "fr" | "pt" if (0.0..2.0).contains(&n) => "one",
+        "fr" | "pt" => "other",
+        _ if integer && n == 1. => "one",
+        _ => "other",
+    }
+}
+
+impl Pattern {
+    /// Text without placeholders
+    pub fn text(text: &str) -> Self {
+        Self(vec![Element::Text(text.to_string())])
+    }
+
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: source.chars().collect(),
+            pos: 0,
+        };
+        let pattern = parser.pattern(false)?;
+        match parser.peek() {
+            None => Ok(pattern),
+            Some(c) => Err(format!("Unexpected `{c}` at {}", parser.pos)),
+        }
+    }
+
+    pub fn format(&self, lang: &str, args: &LocaleArgs) -> String {
+        let mut out = String::new();
+        self.write(lang, args, &mut out);
+        out
+    }
+
+    fn write(&self, lang: &str, args: &LocaleArgs, out: &mut String) {
+        for element in &self.0 {
+            match element {
+                Element::Text(text) => out.push_str(text),
+                Element::Var(var) => match args.get(var) {
+                    Some(value) => out.push_str(&value.to_string()),
+                    // keep the placeholder visible when the arg is missing
+                    None => out.push_str(&format!("{{${var}}}")),
+                },
+                Element::Select {
+                    var,
+                    variants,
+                    default,
+                } => {
+                    let selected = match args.get(var) {
+                        Some(LocaleValue::Number(n)) => {
+                            let category = plural_category(lang, *n);
+                            variants
+                                .iter()
+                                .position(|(key, _)| *key == VariantKey::Number(*n))
+                                .or_else(|| {
+                                    variants.iter().position(|(key, _)| {
+                                        *key == VariantKey::Category(category.to_string())
+                                    })
+                                })
+                        }
+                        Some(LocaleValue::Text(text)) => variants
+                            .iter()
+                            .position(|(key, _)| *key == VariantKey::Category(text.clone())),
+                        None => None,
+                    };
+                    variants[selected.unwrap_or(*default)]
+                        .1
+                        .write(lang, args, out);
+                }
+            }
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek().map_or(false, char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("Expected `{c}` at {}", self.pos))
+        }
+    }
+
+    fn starts_variant(&self) -> bool {
+        match self.peek() {
+            Some('[') => true,
+            Some('*') => self.chars.get(self.pos + 1) == Some(&'['),
+            _ => false,
+        }
+    }
+
+    /// Text and placeables, inside a variant ends on the next variant or `}`
+    fn pattern(&mut self, in_variant: bool) -> Result<Pattern, String> {
+        let mut elements = vec![];
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            if c == '}' || (in_variant && self.starts_variant()) {
+                break;
+            }
+            if c == '{' {
+                if !text.is_empty() {
+                    elements.push(Element::Text(std::mem::take(&mut text)));
+                }
+                self.pos += 1;
+                elements.push(self.placeable()?);
+                continue;
+            }
+            text.push(c);
+            self.pos += 1;
+        }
+        if in_variant {
+            text = text.trim_end().to_string();
+        }
+        if !text.is_empty() {
+            elements.push(Element::Text(text));
+        }
+        Ok(Pattern(elements))
+    }
+
+    fn identifier(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .map_or(false, |c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(format!("Expected a name at {start}"));
+        }
+        Ok(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// Exact number, also with decimals, or plural category
+    fn variant_key(&mut self) -> Result<VariantKey, String> {
+        let start = self.pos;
+        while self.peek().map_or(false, |c| {
+            c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+        }) {
+            self.pos += 1;
+        }
+        let key = self.chars[start..self.pos].iter().collect::<String>();
+        match key.parse::<f64>() {
+            Ok(n) => Ok(VariantKey::Number(n)),
+            Err(_) if key.is_empty() || key.contains('.') => {
+                Err(format!("Expected a number or a category at {start}"))
+            }
+            Err(_) => Ok(VariantKey::Category(key)),
+        }
+    }
+
+    fn placeable(&mut self) -> Result<Element, String> {
+        self.skip_spaces();
+        self.expect('$')?;
+        let var = self.identifier()?;
+        self.skip_spaces();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Element::Var(var));
+        }
+        self.expect('-')?;
+        self.expect('>')?;
+        self.skip_spaces();
+
+        let mut variants = vec![];
+        let mut default = None;
+        while self.starts_variant() {
+            if self.peek() == Some('*') {
+                self.pos += 1;
+                default = Some(variants.len());
+            }
+            self.expect('[')?;
+            self.skip_spaces();
+            let key = self.variant_key()?;
+            self.skip_spaces();
+            self.expect(']')?;
+            self.skip_spaces();
+            variants.push((key, self.pattern(true)?));
+        }
+        self.expect('}')?;
+        let Some(default) = default else {
+            return Err(format!("Missing default `*[..]` variant for ${var}"));
+        };
+        Ok(Element::Select {
+            var,
+            variants,
+            default,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(source: &str, lang: &str, args: &LocaleArgs) -> String {
+        Pattern::parse(source).unwrap().format(lang, args)
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(
+            format("Back to Home", "en-EN", &LocaleArgs::new()),
+            "Back to Home"
+        );
+    }
+
+    #[test]
+    fn placeholders() {
+        let args = LocaleArgs::new().with("score", 42u64).with("name", "crab");
+        assert_eq!(
+            format("The { $name } gave {$score} points", "en-EN", &args),
+            "The crab gave 42 points"
+        );
+        assert_eq!(format("{ $missing }!", "en-EN", &args), "{$missing}!");
+    }
+
+    #[test]
+    fn plurals() {
+        let source = "{ $count -> [0] No bugs [one] One bug *[other] { $count } bugs }";
+        let count = |n: u64| LocaleArgs::new().with("count", n);
+        assert_eq!(format(source, "en-EN", &count(0)), "No bugs");
+        assert_eq!(format(source, "en-EN", &count(1)), "One bug");
+        assert_eq!(format(source, "en-EN", &count(7)), "7 bugs");
+    }
+
+    #[test]
+    fn decimal_keys() {
+        let source = "{ $speed -> [0.5] Half [1.5] One and a half *[other] { $speed }x }";
+        let speed = |n: f32| LocaleArgs::new().with("speed", n);
+        assert_eq!(format(source, "en-EN", &speed(0.5)), "Half");
+        assert_eq!(format(source, "en-EN", &speed(1.5)), "One and a half");
+        assert_eq!(format(source, "en-EN", &speed(2.)), "2x");
+        assert!(Pattern::parse("{ $n -> [1.x] bug *[other] bugs }").is_err());
+        assert!(Pattern::parse("{ $n -> [] bug *[other] bugs }").is_err());
+    }
+
+    #[test]
+    fn plural_rules_by_language() {
+        assert_eq!(plural_category("en-EN", 1.), "one");
+        assert_eq!(plural_category("es-MX", 1.5), "other");
+        assert_eq!(plural_category("fr-FR", 0.), "one");
+        assert_eq!(plural_category("fr", 1.5), "one");
+        assert_eq!(plural_category("fr", 2.), "other");
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert!(Pattern::parse("{ score }").is_err());
+        assert!(Pattern::parse("{ $count -> [one] bug }").is_err());
+        assert!(Pattern::parse("{ $count").is_err());
+        assert!(Pattern::parse("text }").is_err());
+    }
+}
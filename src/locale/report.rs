@@ -0,0 +1,96 @@
+use std::{collections::BTreeSet, sync::Mutex};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use super::{LanguageTag, LocaleAsset, Localizer};
+
+// Keys requested to the dictionaries while the game runs
+static USED_KEYS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+pub(super) fn record_key(key: &str) {
+    let Ok(mut keys) = USED_KEYS.lock() else { return; };
+    if !keys.contains(key) {
+        keys.insert(key.to_string());
+    }
+}
+
+//
+// Keys used by the game or defined by the default language,
+// which are not on each language file. Shown on the inspector
+// and logged when changes, so the translators can fill the gaps
+//
+#[derive(Resource, Debug, Default, Reflect)]
+#[reflect(Resource)]
+pub struct MissingLocaleKeys {
+    pub missing: HashMap<String, Vec<String>>,
+}
+
+pub(super) fn missing_keys(reference: &BTreeSet<String>, asset: &LocaleAsset) -> Vec<String> {
+    reference
+        .iter()
+        .filter(|key| asset.get(key).is_none())
+        .cloned()
+        .collect()
+}
+
+pub(super) fn report_missing_keys(
+    asset_server: Res<AssetServer>,
+    localizer: Localizer,
+    assets: Res<Assets<LocaleAsset>>,
+    mut report: ResMut<MissingLocaleKeys>,
+    // all the languages are kept loaded, not only the active ones
+    mut handles: Local<HashMap<LanguageTag, Handle<LocaleAsset>>>,
+) {
+    let languages = localizer.languages();
+    for entry in languages {
+        handles
+            .entry(entry.tag.clone())
+            .or_insert_with(|| asset_server.load(entry.file.as_str()));
+    }
+    let Some(default) = languages.first() else { return; };
+    let Some(default_asset) = handles.get(&default.tag).and_then(|h| assets.get(h)) else {
+        return;
+    };
+
+    let mut reference = USED_KEYS
+        .lock()
+        .map(|keys| keys.clone())
+        .unwrap_or_default();
+    reference.extend(default_asset.value.keys().cloned());
+
+    let missing = languages
+        .iter()
+        .filter_map(|entry| {
+            let asset = assets.get(handles.get(&entry.tag)?)?;
+            Some((entry.tag.to_string(), missing_keys(&reference, asset)))
+        })
+        .filter(|(_, keys)| !keys.is_empty())
+        .collect::<HashMap<_, _>>();
+    if missing == report.missing {
+        return;
+    }
+    for (tag, keys) in missing.iter() {
+        log::warn!(
+            "Locale {tag} is missing {} keys: {}",
+            keys.len(),
+            keys.join(", ")
+        );
+    }
+    report.missing = missing;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_missing_keys() {
+        let mut asset = LocaleAsset::default();
+        asset.value.insert("best".to_string(), "Best".to_string());
+        let reference = ["best", "button", "time"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(missing_keys(&reference, &asset), vec!["button", "time"]);
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+//
+// BCP-47 language tag, normalized as `language[-Script][-REGION]`,
+// system formats like `es_MX.UTF-8` are accepted too
+//
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+pub struct LanguageTag(String);
+
+impl LanguageTag {
+    pub fn new(raw: &str) -> Self {
+        let raw = raw.split(['.', '@']).next().unwrap_or_default();
+        let tag = raw
+            .split(['-', '_'])
+            .filter(|part| !part.is_empty())
+            .enumerate()
+            .map(|(i, part)| match (i, part.len()) {
+                (0, _) => part.to_lowercase(),
+                (_, 2) => part.to_uppercase(),
+                (_, 4) => {
+                    let (first, rest) = part.split_at(1);
+                    first.to_uppercase() + &rest.to_lowercase()
+                }
+                _ => part.to_lowercase(),
+            })
+            .collect::<Vec<_>>()
+            .join("-");
+        Self(tag)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Primary language subtag, `es` for `es-MX`
+    pub fn language(&self) -> &str {
+        self.0.split('-').next().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for LanguageTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for LanguageTag {
+    fn from(raw: &str) -> Self {
+        Self::new(raw)
+    }
+}
+
+impl<'de> Deserialize<'de> for LanguageTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|raw| Self::new(&raw))
+    }
+}
+
+impl Serialize for LanguageTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+/// Available languages to use for the requested one, from the best match to the default
+pub fn negotiate(
+    requested: &LanguageTag,
+    available: &[LanguageTag],
+    default: &LanguageTag,
+) -> Vec<LanguageTag> {
+    let mut chain: Vec<LanguageTag> = vec![];
+    let exact = available.iter().filter(|tag| *tag == requested);
+    let same_language = available
+        .iter()
+        .filter(|tag| tag.language() == requested.language());
+    let default = available.iter().filter(|tag| *tag == default);
+    for tag in exact.chain(same_language).chain(default) {
+        if !chain.contains(tag) {
+            chain.push(tag.clone());
+        }
+    }
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(raw: &[&str]) -> Vec<LanguageTag> {
+        raw.iter().map(|raw| LanguageTag::new(raw)).collect()
+    }
+
+    #[test]
+    fn normalize_tags() {
+        assert_eq!(LanguageTag::new("es-mx").as_str(), "es-MX");
+        assert_eq!(LanguageTag::new("ES_mx.UTF-8").as_str(), "es-MX");
+        assert_eq!(LanguageTag::new("zh-hant-tw").as_str(), "zh-Hant-TW");
+        assert_eq!(LanguageTag::new("fr").as_str(), "fr");
+        assert_eq!(LanguageTag::new("es-419").language(), "es");
+    }
+
+    #[test]
+    fn exact_match_first() {
+        let available = tags(&["en-EN", "es-ES", "es-MX"]);
+        assert_eq!(
+            negotiate(&"es-MX".into(), &available, &"en-EN".into()),
+            tags(&["es-MX", "es-ES", "en-EN"])
+        );
+    }
+
+    #[test]
+    fn same_language_other_region() {
+        let available = tags(&["en-EN", "es-ES"]);
+        assert_eq!(
+            negotiate(&"es-AR".into(), &available, &"en-EN".into()),
+            tags(&["es-ES", "en-EN"])
+        );
+        assert_eq!(
+            negotiate(&"es".into(), &available, &"en-EN".into()),
+            tags(&["es-ES", "en-EN"])
+        );
+    }
+
+    #[test]
+    fn unknown_language_uses_default() {
+        let available = tags(&["en-EN", "es-ES"]);
+        assert_eq!(
+            negotiate(&"ja-JP".into(), &available, &"en-EN".into()),
+            tags(&["en-EN"])
+        );
+        assert_eq!(
+            negotiate(&"en-US".into(), &available, &"en-EN".into()),
+            tags(&["en-EN"])
+        );
+    }
+}
@@ -0,0 +1,529 @@
+use std::f32::consts::PI;
+
+use bevy::{prelude::*, utils::HashMap};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    game::{BugKilledEvent, ComboEvent},
+    rng::GameRng,
+    settings::Settings,
+};
+
+/// Steps of the colors over the lifetime, with a material shared by the particles
+const FADE_STEPS: usize = 16;
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Particle>()
+            .init_resource::<ParticleRng>()
+            .init_resource::<ParticleAssets>()
+            .add_systems(
+                Startup,
+                |mut assets: ResMut<ParticleAssets>, mut meshes: ResMut<Assets<Mesh>>| {
+                    assets.quad = meshes.add(shape::Quad::new(Vec2::ONE).into());
+                },
+            )
+            .add_systems(
+                Update,
+                (
+                    sync_particle_rng,
+                    spawn_bursts,
+                    emit_particles,
+                    simulate_particles,
+                )
+                    .chain(),
+            );
+    }
+}
+
+//
+// How an emitter spawns its particles and how they move
+//
+#[derive(Clone, Debug, Reflect)]
+pub struct EmitterConfig {
+    /// Particles per second while the emitter is alive
+    pub rate: f32,
+    /// Particles spawned at once when the emitter starts
+    pub burst: u32,
+    /// Seconds emitting, forever when is not set
+    pub duration: Option<f32>,
+    /// Seconds alive of every particle, random between the range
+    pub lifetime: (f32, f32),
+    /// Initial speed, random between the range
+    pub speed: (f32, f32),
+    /// Center of the velocity cone
+    pub direction: Vec3,
+    /// Half angle of the velocity cone, in radians
+    pub spread: f32,
+    pub gravity: Vec3,
+    /// Fraction of the velocity lost per second
+    pub drag: f32,
+    /// Colors over the lifetime, evenly spaced
+    pub colors: Vec<Color>,
+    /// Size at the start and the end of the lifetime
+    pub size: (f32, f32),
+    /// Image of the quads, plain color when is not set
+    pub texture: Option<String>,
+    /// Only emit while the emitter moves
+    pub trail: bool,
+}
+
+impl Default for EmitterConfig {
+    fn default() -> Self {
+        Self {
+            rate: 0.,
+            burst: 0,
+            duration: Some(0.),
+            lifetime: (1., 1.),
+            speed: (1., 1.),
+            direction: Vec3::Z,
+            spread: PI,
+            gravity: Vec3::ZERO,
+            drag: 0.,
+            colors: vec![Color::WHITE],
+            size: (0.2, 0.2),
+            texture: None,
+            trail: false,
+        }
+    }
+}
+
+impl EmitterConfig {
+    /// Pieces of a dead bug, spread over the floor
+    pub fn debris() -> Self {
+        Self {
+            burst: 12,
+            lifetime: (0.4, 0.8),
+            speed: (4., 8.),
+            spread: PI / 2.,
+            drag: 3.,
+            colors: vec![
+                Color::rgba_u8(52, 52, 52, 255),
+                Color::rgba_u8(52, 52, 52, 0),
+            ],
+            size: (0.35, 0.1),
+            ..default()
+        }
+    }
+
+    /// Celebration when the combo multiplier raises
+    pub fn confetti() -> Self {
+        Self {
+            burst: 30,
+            lifetime: (0.8, 1.4),
+            speed: (6., 10.),
+            direction: Vec3::Y,
+            spread: PI / 4.,
+            gravity: Vec3::new(0., -12., 0.),
+            drag: 1.,
+            colors: vec![
+                Color::rgba_u8(0, 133, 255, 255),
+                Color::ORANGE,
+                Color::ORANGE.with_a(0.),
+            ],
+            size: (0.3, 0.3),
+            ..default()
+        }
+    }
+
+    /// Dust behind the fast bugs
+    pub fn trail() -> Self {
+        Self {
+            rate: 30.,
+            duration: None,
+            lifetime: (0.3, 0.5),
+            speed: (0., 0.5),
+            colors: vec![
+                Color::rgba_u8(52, 52, 52, 100),
+                Color::rgba_u8(52, 52, 52, 0),
+            ],
+            size: (0.4, 0.1),
+            trail: true,
+            ..default()
+        }
+    }
+}
+
+//
+// Spawns particles from its global position
+//
+// The emitters with duration are removed once the time ends,
+// the particles already spawned keep alive until their lifetime.
+//
+#[derive(Component, Clone, Debug)]
+pub struct ParticleEmitter {
+    pub config: EmitterConfig,
+    timer: Option<Timer>,
+    started: bool,
+    /// Fraction of particle waiting for the next frame
+    pending: f32,
+    last_pos: Option<Vec3>,
+}
+
+impl ParticleEmitter {
+    pub fn new(config: EmitterConfig) -> Self {
+        Self {
+            timer: config
+                .duration
+                .map(|secs| Timer::from_seconds(secs, TimerMode::Once)),
+            config,
+            started: false,
+            pending: 0.,
+            last_pos: None,
+        }
+    }
+
+    /// Particles to spawn this frame
+    fn emit(&mut self, pos: Vec3, delta: f32) -> u32 {
+        let mut count = 0;
+        if !self.started {
+            self.started = true;
+            count += self.config.burst;
+        }
+        let moved = self
+            .last_pos
+            .replace(pos)
+            .map_or(false, |last| last.distance(pos) > 0.01);
+        if self.config.trail && !moved {
+            return count;
+        }
+        self.pending += self.config.rate * delta;
+        let spawned = self.pending.floor();
+        self.pending -= spawned;
+        count + spawned as u32
+    }
+}
+
+#[derive(Component, Clone, Debug, Reflect)]
+pub struct Particle {
+    pub velocity: Vec3,
+    pub age: f32,
+    pub lifetime: f32,
+    gravity: Vec3,
+    drag: f32,
+    colors: Vec<Color>,
+    size: (f32, f32),
+    /// Index of the materials of its colors on `ParticleAssets`
+    fade: usize,
+}
+
+impl Particle {
+    /// Move the particle, returns if still alive
+    pub fn step(&mut self, translation: &mut Vec3, delta: f32) -> bool {
+        self.velocity += self.gravity * delta;
+        self.velocity *= (1. - self.drag * delta).max(0.);
+        *translation += self.velocity * delta;
+        self.age += delta;
+        self.age < self.lifetime
+    }
+
+    /// From 0 when is spawned to 1 when dies
+    fn progress(&self) -> f32 {
+        (self.age / self.lifetime).clamp(0., 1.)
+    }
+
+    pub fn color(&self) -> Color {
+        gradient(&self.colors, self.progress())
+    }
+
+    /// Step of the shared material of the current color
+    fn fade_step(&self) -> usize {
+        (self.progress() * (FADE_STEPS - 1) as f32).round() as usize
+    }
+
+    pub fn size(&self) -> f32 {
+        let (start, end) = self.size;
+        start + (end - start) * self.progress()
+    }
+}
+
+/// Color at the progress, the colors are evenly spaced from 0 to 1
+fn gradient(colors: &[Color], progress: f32) -> Color {
+    let Some(last) = colors.len().checked_sub(1).filter(|last| *last > 0) else {
+        return colors.first().copied().unwrap_or(Color::WHITE);
+    };
+    let pos = progress * last as f32;
+    let i = (pos.floor() as usize).min(last - 1);
+    let start: Vec4 = colors[i].into();
+    let end: Vec4 = colors[i + 1].into();
+    start.lerp(end, pos - i as f32).into()
+}
+
+/// Random direction inside of the cone around `direction`
+fn cone_direction(direction: Vec3, spread: f32, rng: &mut impl Rng) -> Vec3 {
+    let angle = rng.gen_range(0. ..=spread.max(0.));
+    let turn = rng.gen_range(0. ..PI * 2.);
+    let local = Vec3::new(
+        angle.sin() * turn.cos(),
+        angle.sin() * turn.sin(),
+        angle.cos(),
+    );
+    Quat::from_rotation_arc(Vec3::Z, direction.normalize_or_zero()) * local
+}
+
+/// Seeded from `GameRng` on another stream, so the particles are reproducible
+/// but don't change the bugs of a seed
+#[derive(Resource)]
+struct ParticleRng {
+    seed: Option<u64>,
+    rng: ChaCha8Rng,
+}
+
+impl Default for ParticleRng {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            rng: ChaCha8Rng::seed_from_u64(0),
+        }
+    }
+}
+
+fn sync_particle_rng(game_rng: Res<GameRng>, mut rng: ResMut<ParticleRng>) {
+    let seed = game_rng.seed();
+    if rng.seed == Some(seed) {
+        return;
+    }
+    rng.seed = Some(seed);
+    rng.rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.rng.set_stream(1);
+}
+
+#[derive(Resource, Default)]
+struct ParticleAssets {
+    quad: Handle<Mesh>,
+    /// Materials of every step of the colors of a config, never one per particle
+    fades: Vec<Vec<Handle<StandardMaterial>>>,
+    /// Index on `fades` by the colors and the texture
+    fade_keys: HashMap<(Vec<u32>, Option<String>), usize>,
+}
+
+impl ParticleAssets {
+    /// Fade of the colors of the config, created the first time
+    fn fade(
+        &mut self,
+        config: &EmitterConfig,
+        asset_server: &AssetServer,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> usize {
+        let key = (
+            config.colors.iter().map(Color::as_rgba_u32).collect(),
+            config.texture.clone(),
+        );
+        if let Some(fade) = self.fade_keys.get(&key) {
+            return *fade;
+        }
+        let texture = config
+            .texture
+            .as_ref()
+            .map(|path| asset_server.load(path.as_str()));
+        let steps = (0..FADE_STEPS)
+            .map(|step| {
+                materials.add(StandardMaterial {
+                    base_color: gradient(&config.colors, step as f32 / (FADE_STEPS - 1) as f32),
+                    base_color_texture: texture.clone(),
+                    alpha_mode: AlphaMode::Blend,
+                    unlit: true,
+                    ..default()
+                })
+            })
+            .collect();
+        self.fades.push(steps);
+        self.fade_keys.insert(key, self.fades.len() - 1);
+        self.fades.len() - 1
+    }
+}
+
+fn spawn_bursts(
+    mut cmd: Commands,
+    settings: Res<Settings>,
+    mut killed: EventReader<BugKilledEvent>,
+    mut combos: EventReader<ComboEvent>,
+) {
+    let debris = killed
+        .iter()
+        .map(|event| (event.pos, EmitterConfig::debris()));
+    let confetti = combos
+        .iter()
+        .filter(|event| event.level_up)
+        .map(|event| (event.pos, EmitterConfig::confetti()));
+    for (pos, config) in debris.chain(confetti) {
+        if settings.reduced_motion {
+            continue;
+        }
+        cmd.spawn((
+            TransformBundle::from_transform(Transform::from_translation(pos)),
+            ParticleEmitter::new(config),
+        ));
+    }
+}
+
+fn emit_particles(
+    mut cmd: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut assets: ResMut<ParticleAssets>,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rng: ResMut<ParticleRng>,
+    mut emitters: Query<(Entity, &GlobalTransform, &mut ParticleEmitter)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, transform, mut emitter) in emitters.iter_mut() {
+        let count = if settings.reduced_motion {
+            0
+        } else {
+            emitter.emit(transform.translation(), delta)
+        };
+        let config = &emitter.config;
+        let fade = if count > 0 {
+            assets.fade(config, &asset_server, &mut materials)
+        } else {
+            0
+        };
+        for _ in 0..count {
+            let random = &mut rng.rng;
+            let particle = Particle {
+                velocity: cone_direction(config.direction, config.spread, random)
+                    * random.gen_range(config.speed.0..=config.speed.1),
+                age: 0.,
+                lifetime: random.gen_range(config.lifetime.0..=config.lifetime.1),
+                gravity: config.gravity,
+                drag: config.drag,
+                colors: config.colors.clone(),
+                size: config.size,
+                fade,
+            };
+            cmd.spawn((
+                PbrBundle {
+                    mesh: assets.quad.clone(),
+                    material: assets.fades[fade][0].clone(),
+                    transform: Transform::from_translation(transform.translation())
+                        .with_scale(Vec3::splat(particle.size())),
+                    ..default()
+                },
+                particle,
+            ));
+        }
+        let Some(timer) = emitter.timer.as_mut() else { continue; };
+        if timer.tick(time.delta()).finished() {
+            cmd.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn simulate_particles(
+    mut cmd: Commands,
+    time: Res<Time>,
+    assets: Res<ParticleAssets>,
+    mut particles: Query<(
+        Entity,
+        &mut Particle,
+        &mut Transform,
+        &mut Handle<StandardMaterial>,
+    )>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, material) in particles.iter_mut() {
+        if !particle.step(&mut transform.translation, delta) {
+            cmd.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.scale = Vec3::splat(particle.size());
+        let step = assets
+            .fades
+            .get(particle.fade)
+            .and_then(|steps| steps.get(particle.fade_step()));
+        if let Some(step) = step.filter(|step| **step != *material) {
+            *material = step.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn particle(colors: Vec<Color>) -> Particle {
+        Particle {
+            velocity: Vec3::X,
+            age: 0.,
+            lifetime: 1.,
+            gravity: Vec3::ZERO,
+            drag: 0.,
+            colors,
+            size: (1., 0.),
+            fade: 0,
+        }
+    }
+
+    #[test]
+    fn gravity_and_drag() {
+        let mut pos = Vec3::ZERO;
+        let mut falling = Particle {
+            gravity: Vec3::new(0., -10., 0.),
+            ..particle(vec![])
+        };
+        assert!(falling.step(&mut pos, 0.5));
+        assert_eq!(falling.velocity, Vec3::new(1., -5., 0.));
+        assert_eq!(pos, Vec3::new(0.5, -2.5, 0.));
+
+        let mut slow = Particle {
+            drag: 1.,
+            ..particle(vec![])
+        };
+        slow.step(&mut pos, 0.5);
+        assert_eq!(slow.velocity, Vec3::X * 0.5);
+        assert!(!slow.step(&mut pos, 0.5));
+    }
+
+    #[test]
+    fn color_and_size_over_lifetime() {
+        let mut p = particle(vec![Color::WHITE, Color::BLACK, Color::WHITE.with_a(0.)]);
+        assert_eq!(p.color(), Color::WHITE);
+        assert_eq!(p.size(), 1.);
+        p.age = 0.5;
+        assert_eq!(p.color(), Color::BLACK);
+        assert_eq!(p.size(), 0.5);
+        p.age = 1.;
+        assert_eq!(p.color(), Color::WHITE.with_a(0.));
+        assert_eq!(particle(vec![Color::RED]).color(), Color::RED);
+    }
+
+    #[test]
+    fn fade_steps_over_lifetime() {
+        let mut p = particle(vec![Color::WHITE, Color::BLACK]);
+        assert_eq!(p.fade_step(), 0);
+        p.age = 0.5;
+        assert_eq!(p.fade_step(), FADE_STEPS / 2);
+        p.age = 2.;
+        assert_eq!(p.fade_step(), FADE_STEPS - 1);
+    }
+
+    #[test]
+    fn directions_inside_of_the_cone() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..100 {
+            let dir = cone_direction(Vec3::Y, PI / 4., &mut rng);
+            assert!((dir.length() - 1.).abs() < 1e-4);
+            assert!(dir.angle_between(Vec3::Y) <= PI / 4. + 1e-4);
+        }
+    }
+
+    #[test]
+    fn rate_and_burst() {
+        let mut emitter = ParticleEmitter::new(EmitterConfig {
+            rate: 10.,
+            burst: 5,
+            ..default()
+        });
+        assert_eq!(emitter.emit(Vec3::ZERO, 0.25), 7);
+        assert_eq!(emitter.emit(Vec3::ZERO, 0.25), 3);
+
+        let mut trail = ParticleEmitter::new(EmitterConfig::trail());
+        assert_eq!(trail.emit(Vec3::ZERO, 1.), 0);
+        assert_eq!(trail.emit(Vec3::X, 1.), 30);
+    }
+}
@@ -0,0 +1,468 @@
+use std::f32::consts::TAU;
+
+use bevy::{math::vec3, prelude::*};
+use rand::{Rng, RngCore};
+use serde::Deserialize;
+
+use crate::helper::{inside_screen, out_of_screen, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+//
+// Generate the points where a bug walks
+//
+// The first and last points are out of screen, the curves are
+// returned already sampled so the bug only follows straight segments.
+//
+pub trait PathGenerator {
+    fn generate(&self, rnd: &mut dyn RngCore) -> Vec<Vec3>;
+}
+
+//
+// How the bug walks on screen, used from data
+//
+#[derive(Clone, Copy, Debug, Default, Deserialize, Reflect, PartialEq, Eq)]
+pub enum PathStyle {
+    /// Random points inside of screen
+    #[default]
+    Random,
+    /// Cross the screen from side to side
+    Straight,
+    /// Smooth curve passing by random points
+    CatmullRom,
+    /// Smooth curve near of random points
+    Bezier,
+    ZigZag,
+    /// Turn around to the center and leave the screen
+    Spiral,
+    /// Walk following the borders of the screen
+    EdgeHugging,
+}
+
+impl PathGenerator for PathStyle {
+    fn generate(&self, rnd: &mut dyn RngCore) -> Vec<Vec3> {
+        match self {
+            PathStyle::Random => RandomPolyline::default().generate(rnd),
+            PathStyle::Straight => Straight.generate(rnd),
+            PathStyle::CatmullRom => CatmullRom::default().generate(rnd),
+            PathStyle::Bezier => Bezier::default().generate(rnd),
+            PathStyle::ZigZag => ZigZag::default().generate(rnd),
+            PathStyle::Spiral => Spiral::default().generate(rnd),
+            PathStyle::EdgeHugging => EdgeHugging::default().generate(rnd),
+        }
+    }
+}
+
+//
+// Straight segments between random points
+//
+pub struct RandomPolyline {
+    pub points: usize,
+}
+
+impl Default for RandomPolyline {
+    fn default() -> Self {
+        Self { points: 7 }
+    }
+}
+
+impl PathGenerator for RandomPolyline {
+    fn generate(&self, rnd: &mut dyn RngCore) -> Vec<Vec3> {
+        let mut points = vec![out_of_screen(rnd)];
+        for _ in 0..self.points {
+            points.push(inside_screen(rnd));
+        }
+        points.push(out_of_screen(rnd));
+        points
+    }
+}
+
+pub struct Straight;
+
+impl PathGenerator for Straight {
+    fn generate(&self, rnd: &mut dyn RngCore) -> Vec<Vec3> {
+        let start = out_of_screen(rnd);
+        // end on the opposite side of the start
+        let mut end = out_of_screen(rnd);
+        end.x = end.x.abs() * -start.x.signum();
+        vec![start, end]
+    }
+}
+
+//
+// Catmull-Rom spline passing by every random point
+//
+pub struct CatmullRom {
+    pub points: usize,
+    pub resolution: usize,
+}
+
+impl Default for CatmullRom {
+    fn default() -> Self {
+        Self {
+            points: 5,
+            resolution: 12,
+        }
+    }
+}
+
+impl PathGenerator for CatmullRom {
+    fn generate(&self, rnd: &mut dyn RngCore) -> Vec<Vec3> {
+        let control = RandomPolyline {
+            points: self.points,
+        }
+        .generate(rnd);
+        catmull_rom(&control, self.resolution)
+    }
+}
+
+pub fn catmull_rom(control: &[Vec3], resolution: usize) -> Vec<Vec3> {
+    let Some(first) = control.first() else { return Vec::new(); };
+    let last = control.len() - 1;
+    let mut points = vec![*first];
+    for i in 0..last {
+        let p0 = control[i.saturating_sub(1)];
+        let p1 = control[i];
+        let p2 = control[i + 1];
+        let p3 = control[(i + 2).min(last)];
+        for step in 1..=resolution {
+            let t = step as f32 / resolution as f32;
+            let (t2, t3) = (t * t, t * t * t);
+            points.push(
+                0.5 * (2. * p1
+                    + (p2 - p0) * t
+                    + (2. * p0 - 5. * p1 + 4. * p2 - p3) * t2
+                    + (3. * p1 - p0 - 3. * p2 + p3) * t3),
+            );
+        }
+    }
+    points
+}
+
+//
+// Quadratic Bezier curves between the middle of each segment,
+// the random points are used as control points
+//
+pub struct Bezier {
+    pub points: usize,
+    pub resolution: usize,
+}
+
+impl Default for Bezier {
+    fn default() -> Self {
+        Self {
+            points: 6,
+            resolution: 12,
+        }
+    }
+}
+
+impl PathGenerator for Bezier {
+    fn generate(&self, rnd: &mut dyn RngCore) -> Vec<Vec3> {
+        let control = RandomPolyline {
+            points: self.points,
+        }
+        .generate(rnd);
+        bezier(&control, self.resolution)
+    }
+}
+
+pub fn bezier(control: &[Vec3], resolution: usize) -> Vec<Vec3> {
+    if control.len() < 3 {
+        return control.to_vec();
+    }
+    let last = control.len() - 1;
+    let mut points = vec![control[0]];
+    for i in 1..last {
+        let start = if i == 1 {
+            control[0]
+        } else {
+            control[i - 1].lerp(control[i], 0.5)
+        };
+        let end = if i == last - 1 {
+            control[last]
+        } else {
+            control[i].lerp(control[i + 1], 0.5)
+        };
+        for step in 1..=resolution {
+            let t = step as f32 / resolution as f32;
+            let a = start.lerp(control[i], t);
+            let b = control[i].lerp(end, t);
+            points.push(a.lerp(b, t));
+        }
+    }
+    points
+}
+
+//
+// Cross the screen going up and down
+//
+pub struct ZigZag {
+    pub amplitude: f32,
+    pub segments: usize,
+}
+
+impl Default for ZigZag {
+    fn default() -> Self {
+        Self {
+            amplitude: 4.,
+            segments: 8,
+        }
+    }
+}
+
+impl PathGenerator for ZigZag {
+    fn generate(&self, rnd: &mut dyn RngCore) -> Vec<Vec3> {
+        let ends = Straight.generate(rnd);
+        let (start, end) = (ends[0], ends[1]);
+        let limit = (SCREEN_HEIGHT - self.amplitude).max(0.);
+        let center = rnd.gen_range(-limit..=limit);
+        let direction = -start.x.signum();
+        let mut points = vec![start];
+        for i in 0..=self.segments {
+            let x = -direction * SCREEN_WIDTH
+                + direction * 2. * SCREEN_WIDTH * i as f32 / self.segments.max(1) as f32;
+            let side = if i % 2 == 0 { 1. } else { -1. };
+            points.push(vec3(x, center + side * self.amplitude, 0.));
+        }
+        points.push(end);
+        points
+    }
+}
+
+//
+// Turn around to the center and leave the screen
+//
+pub struct Spiral {
+    pub turns: f32,
+    pub resolution: usize,
+}
+
+impl Default for Spiral {
+    fn default() -> Self {
+        Self {
+            turns: 2.,
+            resolution: 24,
+        }
+    }
+}
+
+impl PathGenerator for Spiral {
+    fn generate(&self, rnd: &mut dyn RngCore) -> Vec<Vec3> {
+        let start = out_of_screen(rnd);
+        let center = vec3(rnd.gen_range(-4.0..=4.), rnd.gen_range(-2.0..=2.), 0.);
+        let clockwise = if rnd.gen_bool(0.5) { 1. } else { -1. };
+        let angle = (start.y - center.y).atan2(start.x - center.x);
+        let steps = (self.turns * self.resolution as f32).max(1.) as usize;
+        let mut points = vec![start];
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let radius = (1. - t) * SCREEN_HEIGHT;
+            let angle = angle + clockwise * t * self.turns * TAU;
+            points.push(
+                center
+                    + vec3(
+                        angle.cos() * radius * SCREEN_WIDTH / SCREEN_HEIGHT,
+                        angle.sin() * radius,
+                        0.,
+                    ),
+            );
+        }
+        points.push(out_of_screen(rnd));
+        points
+    }
+}
+
+//
+// Walk following the borders of the screen
+//
+pub struct EdgeHugging {
+    pub margin: f32,
+    pub laps: usize,
+}
+
+impl Default for EdgeHugging {
+    fn default() -> Self {
+        Self {
+            margin: 2.,
+            laps: 1,
+        }
+    }
+}
+
+impl PathGenerator for EdgeHugging {
+    fn generate(&self, rnd: &mut dyn RngCore) -> Vec<Vec3> {
+        let (w, h) = (SCREEN_WIDTH - self.margin, SCREEN_HEIGHT - self.margin);
+        // corners in clockwise order
+        let mut corners = vec![
+            vec3(-w, h, 0.),
+            vec3(w, h, 0.),
+            vec3(w, -h, 0.),
+            vec3(-w, -h, 0.),
+        ];
+        if rnd.gen_bool(0.5) {
+            corners.reverse();
+        }
+        let start = out_of_screen(rnd);
+        // enter by the nearest corner
+        let first = (0..corners.len())
+            .min_by(|a, b| {
+                corners[*a]
+                    .distance(start)
+                    .total_cmp(&corners[*b].distance(start))
+            })
+            .unwrap_or_default();
+        let mut points = vec![start];
+        for i in 0..=corners.len() * self.laps.max(1) {
+            points.push(corners[(first + i) % corners.len()]);
+        }
+        // leave by the same corner
+        let exit = *points.last().unwrap();
+        points.push(vec3(exit.x.signum() * 25., exit.y.signum() * 20., 0.));
+        points
+    }
+}
+
+//
+// Walk a path at constant speed, sampling by arc length
+//
+#[derive(Clone, Debug, Default, Reflect)]
+pub struct ArcLengthPath {
+    points: Vec<Vec3>,
+    /// Distance from the start to every point
+    lengths: Vec<f32>,
+}
+
+impl ArcLengthPath {
+    pub fn new(points: Vec<Vec3>) -> Self {
+        let mut lengths = Vec::with_capacity(points.len());
+        let mut total = 0.;
+        for (i, point) in points.iter().enumerate() {
+            if i > 0 {
+                total += point.distance(points[i - 1]);
+            }
+            lengths.push(total);
+        }
+        Self { points, lengths }
+    }
+
+    pub fn points(&self) -> &[Vec3] {
+        &self.points
+    }
+
+    pub fn length(&self) -> f32 {
+        self.lengths.last().copied().unwrap_or_default()
+    }
+
+    /// Position and direction at the distance from the start,
+    /// `None` when the distance is out of the path
+    pub fn sample(&self, distance: f32) -> Option<(Vec3, Vec3)> {
+        if self.points.len() < 2 || distance < 0. || distance > self.length() {
+            return None;
+        }
+        let next = self
+            .lengths
+            .partition_point(|length| *length < distance)
+            .clamp(1, self.points.len() - 1);
+        let (a, b) = (self.points[next - 1], self.points[next]);
+        let segment = self.lengths[next] - self.lengths[next - 1];
+        let t = if segment > 0. {
+            (distance - self.lengths[next - 1]) / segment
+        } else {
+            0.
+        };
+        Some((a.lerp(b, t), (b - a).normalize_or_zero()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    const STYLES: [PathStyle; 7] = [
+        PathStyle::Random,
+        PathStyle::Straight,
+        PathStyle::CatmullRom,
+        PathStyle::Bezier,
+        PathStyle::ZigZag,
+        PathStyle::Spiral,
+        PathStyle::EdgeHugging,
+    ];
+
+    fn is_out_of_screen(point: Vec3) -> bool {
+        point.x.abs() >= SCREEN_WIDTH || point.y.abs() >= SCREEN_HEIGHT
+    }
+
+    #[test]
+    fn paths_start_and_end_out_of_screen() {
+        let mut rnd = StdRng::seed_from_u64(404);
+        for style in STYLES {
+            for _ in 0..50 {
+                let points = style.generate(&mut rnd);
+                assert!(points.len() >= 2, "{style:?}");
+                assert!(is_out_of_screen(points[0]), "{style:?}: {points:?}");
+                assert!(
+                    is_out_of_screen(*points.last().unwrap()),
+                    "{style:?}: {points:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn curves_pass_by_control_points() {
+        let control = [
+            vec3(-25., 0., 0.),
+            vec3(0., 10., 0.),
+            vec3(10., -5., 0.),
+            vec3(25., 0., 0.),
+        ];
+        let points = catmull_rom(&control, 8);
+        for point in control {
+            assert!(points.iter().any(|p| p.distance(point) < 1e-4), "{point}");
+        }
+
+        let points = bezier(&control, 8);
+        assert_eq!(points.first(), control.first());
+        assert!(points.last().unwrap().distance(control[3]) < 1e-4);
+    }
+
+    #[test]
+    fn sample_by_arc_length() {
+        let path = ArcLengthPath::new(vec![
+            vec3(0., 0., 0.),
+            vec3(1., 0., 0.),
+            vec3(1., 0., 0.),
+            vec3(1., 9., 0.),
+        ]);
+        let near = |distance: f32, pos: Vec3, dir: Vec3| {
+            let (p, d) = path.sample(distance).unwrap();
+            assert!(p.distance(pos) < 1e-4, "{p} != {pos}");
+            assert!(d.distance(dir) < 1e-4, "{d} != {dir}");
+        };
+        assert_eq!(path.length(), 10.);
+        near(0., Vec3::ZERO, Vec3::X);
+        near(0.5, vec3(0.5, 0., 0.), Vec3::X);
+        near(5., vec3(1., 4., 0.), Vec3::Y);
+        near(10., vec3(1., 9., 0.), Vec3::Y);
+        assert_eq!(path.sample(10.1), None);
+        assert_eq!(path.sample(-0.1), None);
+    }
+
+    #[test]
+    fn constant_speed_on_curves() {
+        let mut rnd = StdRng::seed_from_u64(404);
+        for style in STYLES {
+            let path = ArcLengthPath::new(style.generate(&mut rnd));
+            let step = 0.25;
+            let mut last = path.sample(0.).unwrap().0;
+            let mut distance = step;
+            while let Some((pos, _)) = path.sample(distance) {
+                // never walk more than the step, only less on the corners
+                assert!(pos.distance(last) <= step + 1e-3, "{style:?}");
+                last = pos;
+                distance += step;
+            }
+        }
+    }
+}
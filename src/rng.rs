@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use rand::{thread_rng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::config::GameConfig;
+
+//
+// Random generator used by every decision of the game
+//
+// The same seed gives the same bugs and paths on every platform,
+// useful for daily challenges, bug reports and tests.
+//
+#[derive(Resource, Clone)]
+pub struct GameRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// New random seed, when the config has no seed
+    pub fn random() -> Self {
+        Self::new(thread_rng().gen())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Start again the sequence of the current seed
+    pub fn reset(&mut self) {
+        self.rng = ChaCha8Rng::seed_from_u64(self.seed);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        let rng = app
+            .world
+            .get_resource::<GameConfig>()
+            .and_then(|config| config.seed)
+            .map_or_else(GameRng::random, GameRng::new);
+        log::info!("Game seed: {}", rng.seed());
+        app.insert_resource(rng).add_systems(
+            PreUpdate,
+            apply_seed.run_if(resource_changed::<GameConfig>()),
+        );
+    }
+}
+
+//
+// Seed of the config asset or changed at runtime
+//
+fn apply_seed(config: Res<GameConfig>, mut rng: ResMut<GameRng>) {
+    let Some(seed) = config.seed else { return; };
+    if seed != rng.seed {
+        log::info!("Game seed: {seed}");
+        *rng = GameRng::new(seed);
+    }
+}
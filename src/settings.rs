@@ -0,0 +1,229 @@
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::AudioBus,
+    locale::{LanguageTag, Locale, LocaleChanged},
+    storage::StorageResource,
+};
+
+const SETTINGS_KEY: &str = "settings";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_settings).add_systems(
+            Update,
+            apply_settings.run_if(resource_changed::<Settings>()),
+        );
+    }
+}
+
+//
+// Preferences of the player, persisted between sessions
+// and applied as soon as are changed
+//
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Settings {
+    /// Master volume, from 0 to 1
+    pub volume: f32,
+    pub music_volume: f32,
+    /// Volume of the sound effects
+    pub sfx_volume: f32,
+    /// Chosen language, the one of the system when is not set
+    pub language: Option<LanguageTag>,
+    pub fullscreen: bool,
+    /// Skip the bounce and floating animations
+    pub reduced_motion: bool,
+    /// Size of the bug hitboxes, from 1 to 2
+    pub hitbox_scale: f32,
+    /// Speed of the bugs, from 0.5 to 1
+    pub speed_scale: f32,
+    /// Solid colors on the UI, see `Palette::HIGH_CONTRAST`
+    pub high_contrast: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            volume: 1.,
+            music_volume: 1.,
+            sfx_volume: 1.,
+            language: None,
+            fullscreen: false,
+            reduced_motion: false,
+            hitbox_scale: 1.,
+            speed_scale: 1.,
+            high_contrast: false,
+        }
+    }
+}
+
+impl Settings {
+    fn volume_mut(&mut self, bus: AudioBus) -> &mut f32 {
+        match bus {
+            AudioBus::Master => &mut self.volume,
+            AudioBus::Music => &mut self.music_volume,
+            AudioBus::Sfx => &mut self.sfx_volume,
+        }
+    }
+
+    /// Volume of the bus, without the master volume
+    pub fn volume(&self, bus: AudioBus) -> f32 {
+        match bus {
+            AudioBus::Master => self.volume,
+            AudioBus::Music => self.music_volume,
+            AudioBus::Sfx => self.sfx_volume,
+        }
+    }
+
+    /// Volume of the bus, with the master volume applied
+    pub fn output_volume(&self, bus: AudioBus) -> f32 {
+        match bus {
+            AudioBus::Master => self.volume,
+            _ => self.volume * self.volume(bus),
+        }
+    }
+
+    /// Raise or lower the volume of the bus by steps of 10%
+    pub fn change_volume(&mut self, bus: AudioBus, steps: i8) {
+        let volume = self.volume_mut(bus);
+        *volume = step_value(*volume, steps, 10., (0., 1.));
+    }
+
+    /// Grow or shrink the hitboxes by steps of 25%
+    pub fn change_hitbox_scale(&mut self, steps: i8) {
+        self.hitbox_scale = step_value(self.hitbox_scale, steps, 4., (1., 2.));
+    }
+
+    /// Speed up or slow down the bugs by steps of 10%
+    pub fn change_speed_scale(&mut self, steps: i8) {
+        self.speed_scale = step_value(self.speed_scale, steps, 10., (0.5, 1.));
+    }
+}
+
+/// Move the value by steps of `1 / per_unit`, kept inside of the range
+fn step_value(value: f32, steps: i8, per_unit: f32, (min, max): (f32, f32)) -> f32 {
+    let level = (value * per_unit).round() + steps as f32;
+    (level / per_unit).clamp(min, max)
+}
+
+// Inserted on startup, so is available on `Update`
+fn load_settings(
+    mut cmd: Commands,
+    storage: Res<StorageResource>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let settings = storage
+        .0
+        .load(SETTINGS_KEY)
+        .and_then(|data| {
+            ron::from_str::<Settings>(&data)
+                .map_err(|e| log::error!("Invalid settings: {e}"))
+                .ok()
+        })
+        .unwrap_or_else(|| Settings {
+            // the launcher decides until the player changes it
+            fullscreen: window
+                .get_single()
+                .is_ok_and(|window| window.mode != WindowMode::Windowed),
+            ..default()
+        });
+    log::info!("Settings: {settings:?}");
+    cmd.insert_resource(settings);
+}
+
+//
+// The loaded settings are applied on the first run too, but only
+// the changes of the player are saved
+//
+fn apply_settings(
+    settings: Res<Settings>,
+    storage: Res<StorageResource>,
+    locale: Res<Locale>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    mut language: EventWriter<LocaleChanged>,
+) {
+    if let Ok(mut window) = window.get_single_mut() {
+        let mode = if settings.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        if window.mode != mode {
+            window.mode = mode;
+        }
+    }
+    if let Some(tag) = settings.language.as_ref() {
+        if *tag != locale.requested {
+            language.send(LocaleChanged(tag.clone()));
+        }
+    }
+
+    if settings.is_added() {
+        return;
+    }
+    match ron::to_string(&*settings) {
+        Ok(data) => storage.0.save(SETTINGS_KEY, &data),
+        Err(e) => log::error!("Unable to serialize settings: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_steps_are_clamped() {
+        let mut settings = Settings::default();
+        settings.change_volume(AudioBus::Master, 1);
+        assert_eq!(settings.volume, 1.);
+        settings.change_volume(AudioBus::Master, -3);
+        assert_eq!(settings.volume, 0.7);
+        settings.change_volume(AudioBus::Master, -20);
+        assert_eq!(settings.volume, 0.);
+    }
+
+    #[test]
+    fn accessibility_scales_are_clamped() {
+        let mut settings = Settings::default();
+        settings.change_hitbox_scale(-1);
+        assert_eq!(settings.hitbox_scale, 1.);
+        settings.change_hitbox_scale(2);
+        assert_eq!(settings.hitbox_scale, 1.5);
+        settings.change_hitbox_scale(10);
+        assert_eq!(settings.hitbox_scale, 2.);
+
+        settings.change_speed_scale(-3);
+        assert_eq!(settings.speed_scale, 0.7);
+        settings.change_speed_scale(-10);
+        assert_eq!(settings.speed_scale, 0.5);
+    }
+
+    #[test]
+    fn buses_are_scaled_by_master() {
+        let mut settings = Settings::default();
+        settings.change_volume(AudioBus::Master, -5);
+        settings.change_volume(AudioBus::Music, -2);
+        assert_eq!(settings.volume(AudioBus::Music), 0.8);
+        assert_eq!(settings.output_volume(AudioBus::Music), 0.4);
+        assert_eq!(settings.output_volume(AudioBus::Sfx), 0.5);
+    }
+
+    #[test]
+    fn saved_settings_keep_missing_fields() {
+        let settings =
+            ron::from_str::<Settings>("(volume: 0.4, language: Some(\"es-mx\"))").unwrap();
+        assert_eq!(settings.volume, 0.4);
+        assert_eq!(settings.language, Some("es-MX".into()));
+        assert!(!settings.fullscreen);
+
+        let data = ron::to_string(&settings).unwrap();
+        assert_eq!(ron::from_str::<Settings>(&data).unwrap(), settings);
+    }
+}
@@ -0,0 +1,163 @@
+use bevy::{app::AppExit, prelude::*, utils::HashMap, window::WindowFocused};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{BugEntityClickedEvent, BugKilledEvent, MissEvent, RestartEvent, ScoreTextResource},
+    storage::StorageResource,
+    GameState,
+};
+
+const STATS_KEY: &str = "stats";
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Accuracy>()
+            .add_systems(Startup, load_stats)
+            .add_systems(
+                OnTransition {
+                    from: GameState::MainPage,
+                    to: GameState::Game,
+                },
+                |mut stats: ResMut<PlayerStats>| {
+                    stats.sessions += 1;
+                },
+            )
+            .add_systems(
+                Update,
+                (
+                    reset_accuracy.run_if(on_event::<RestartEvent>()),
+                    count_hits,
+                    count_kills,
+                    best_score_print,
+                )
+                    .chain(),
+            )
+            // after the exit requests of the frame
+            .add_systems(Last, save_stats);
+    }
+}
+
+//
+// Statistics persisted between sessions
+//
+#[derive(Resource, Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct PlayerStats {
+    pub best_score: u64,
+    pub total_kills: u64,
+    pub sessions: u64,
+    pub hits: u64,
+    pub misses: u64,
+    /// Kills by bug archetype
+    pub kills: HashMap<String, u64>,
+}
+
+//
+// Clicks on bugs over all the clicks of the current round
+//
+#[derive(Resource, Debug, Default, Clone)]
+pub(crate) struct Accuracy {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl Accuracy {
+    pub fn ratio(&self) -> f32 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            return 1.;
+        }
+        self.hits as f32 / total as f32
+    }
+}
+
+//
+// Best Score Text Identifier
+//
+#[derive(Component)]
+pub struct BestScoreText;
+
+// Inserted on startup, so is available on `Update`
+fn load_stats(mut cmd: Commands, storage: Res<StorageResource>) {
+    let stats = storage
+        .0
+        .load(STATS_KEY)
+        .and_then(|data| {
+            ron::from_str::<PlayerStats>(&data)
+                .map_err(|e| log::error!("Invalid stats: {e}"))
+                .ok()
+        })
+        .unwrap_or_default();
+    log::info!("Player stats: {stats:?}");
+    cmd.insert_resource(stats);
+}
+
+//
+// The stats change on every hit, so they are saved when the state changes,
+// the window loses the focus or the app exits, not on every change
+//
+fn save_stats(
+    stats: Res<PlayerStats>,
+    storage: Res<StorageResource>,
+    state: Res<State<GameState>>,
+    mut focus: EventReader<WindowFocused>,
+    mut exit: EventReader<AppExit>,
+    mut dirty: Local<bool>,
+) {
+    // the loaded stats are already saved
+    *dirty |= stats.is_changed() && !stats.is_added();
+    let lost_focus = focus.iter().any(|event| !event.focused);
+    let exiting = exit.iter().count() > 0;
+    if !*dirty || !(state.is_changed() || lost_focus || exiting) {
+        return;
+    }
+    *dirty = false;
+    match ron::to_string(&*stats) {
+        Ok(data) => storage.0.save(STATS_KEY, &data),
+        Err(e) => log::error!("Unable to serialize stats: {e}"),
+    }
+}
+
+fn reset_accuracy(mut accuracy: ResMut<Accuracy>) {
+    *accuracy = Accuracy::default();
+}
+
+fn count_hits(
+    state: Res<State<GameState>>,
+    mut stats: ResMut<PlayerStats>,
+    mut accuracy: ResMut<Accuracy>,
+    mut hits: EventReader<BugEntityClickedEvent>,
+    mut misses: EventReader<MissEvent>,
+) {
+    let (hits, misses) = (hits.iter().count() as u64, misses.iter().count() as u64);
+    if *state.get() != GameState::Game || hits + misses == 0 {
+        return;
+    }
+    accuracy.hits += hits;
+    accuracy.misses += misses;
+    stats.hits += hits;
+    stats.misses += misses;
+}
+
+fn count_kills(
+    mut stats: ResMut<PlayerStats>,
+    score: Res<ScoreTextResource>,
+    mut killed: EventReader<BugKilledEvent>,
+) {
+    for e in killed.iter() {
+        stats.total_kills += 1;
+        *stats.kills.entry(e.archetype.clone()).or_default() += 1;
+    }
+    if score.0 > stats.best_score {
+        stats.best_score = score.0;
+    }
+}
+
+fn best_score_print(mut text: Query<&mut Text, With<BestScoreText>>, stats: Res<PlayerStats>) {
+    let Ok(mut text) = text.get_single_mut() else { return; };
+    if let Some(section) = text.sections.last_mut() {
+        section.value = stats.best_score.to_string();
+    }
+}
@@ -0,0 +1,67 @@
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
+
+use bevy::prelude::*;
+
+//
+// Key/value storage which persists between sessions
+//
+// Every launcher gives its own implementation, like a file on native
+// and Android or the `localStorage` on web.
+//
+pub trait Storage: Send + Sync + 'static {
+    fn load(&self, key: &str) -> Option<String>;
+    fn save(&self, key: &str, value: &str);
+}
+
+#[derive(Resource)]
+pub(crate) struct StorageResource(pub Box<dyn Storage>);
+
+//
+// One file per key inside of a directory
+//
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.ron"))
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&self, key: &str) -> Option<String> {
+        std::fs::read_to_string(self.path(key)).ok()
+    }
+
+    fn save(&self, key: &str, value: &str) {
+        if let Err(e) =
+            std::fs::create_dir_all(&self.dir).and_then(|_| std::fs::write(self.path(key), value))
+        {
+            log::error!("Unable to save {key}: {e}");
+        }
+    }
+}
+
+//
+// Storage only alive while the app is running, for tests
+// and the systems without a data directory
+//
+#[derive(Default)]
+pub struct MemoryStorage(Mutex<HashMap<String, String>>);
+
+impl Storage for MemoryStorage {
+    fn load(&self, key: &str) -> Option<String> {
+        self.0.lock().ok()?.get(key).cloned()
+    }
+
+    fn save(&self, key: &str, value: &str) {
+        if let Ok(mut values) = self.0.lock() {
+            values.insert(key.to_string(), value.to_string());
+        }
+    }
+}
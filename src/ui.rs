@@ -1,60 +1,116 @@
 use std::time::Duration;
 
-use bevy::{prelude::*, window::PrimaryWindow};
-use bevy_tweening::{Animator, Delay, EaseFunction, Tween, TweenCompleted};
+use bevy::{
+    a11y::{
+        accesskit::{NodeBuilder, Role},
+        AccessibilityNode,
+    },
+    prelude::*,
+    ui::widget::Label,
+    window::PrimaryWindow,
+};
+use bevy_tweening::{
+    component_animator_system, AnimationSystem, Animator, Delay, EaseFunction, Tween,
+    TweenCompleted,
+};
 
 use crate::{
-    game::ScoreText,
-    lens::{GameTextColorLens, InstanceLens},
-    locale::LocaleAsset,
-    LocaleLangs, OpenLinkResource, HOME_URL, TIME_WAIT_TO_START,
+    audio::AudioBus,
+    config::{config_ready, GameConfig, GameMode},
+    game::{PauseEvent, RestartEvent, RoundText, ScoreText},
+    input::{ActionEvent, GameAction},
+    lens::ThemeAlphaLens,
+    locale::{LanguageTag, LocalizedText, Localizer},
+    settings::Settings,
+    stats::BestScoreText,
+    GameState, OpenLinkResource,
 };
 
+use self::{
+    game_over::{remove_game_over, setup_game_over},
+    pause::{remove_pause, setup_pause},
+    settings::{close_settings, settings_values_print, toggle_settings, SettingsPanel},
+    theme::apply_theme,
+};
+
+mod game_over;
+mod pause;
+mod settings;
+mod theme;
+
+pub(crate) use theme::{Palette, ThemeColor, ThemeRole};
+
 #[derive(Component)]
 struct RemovableUI;
 
+//
+// What a button does when is pressed
+//
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ButtonAction {
+    Home,
+    Restart,
+    Resume,
+    Language(LanguageTag),
+    Settings,
+    CloseSettings,
+    /// Steps of 10% to raise or lower the volume of the bus
+    Volume(AudioBus, i8),
+    Fullscreen,
+    ReducedMotion,
+    /// Steps of 25% to grow or shrink the bug hitboxes
+    HitboxScale(i8),
+    /// Steps of 10% to slow down or speed up the bugs
+    SpeedScale(i8),
+    HighContrast,
+}
+
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Startup,
-            |mut cmd: Commands, asset_server: Res<AssetServer>| {
-                cmd.insert_resource(UiResources {
-                    languages: vec![
-                        asset_server.load("locale/en-EN.locale"), // english dictionary
-                        asset_server.load("locale/es-ES.locale"), //spanish dictionary
-                    ],
-                });
-            },
-        )
-        .add_systems(
-            Update,
-            (setup_ui, button_system, remove_screen.run_if(run_if_anim)),
-        );
+        app.init_resource::<SettingsPanel>()
+            .add_systems(
+                Update,
+                (
+                    // the texts wait the seconds of the config asset
+                    setup_ui.run_if(config_ready),
+                    button_system,
+                    overlay_actions,
+                    toggle_settings.run_if(resource_changed::<SettingsPanel>()),
+                    settings_values_print,
+                    score_accessibility,
+                    remove_screen.run_if(run_if_anim),
+                    component_animator_system::<ThemeColor>
+                        .in_set(AnimationSystem::AnimationUpdate),
+                ),
+            )
+            // after the spawns of `Update`, so the new texts are never shown uncolored
+            .add_systems(PostUpdate, (language_highlight, apply_theme).chain())
+            .add_systems(OnEnter(GameState::GameOver), setup_game_over)
+            .add_systems(OnExit(GameState::GameOver), remove_game_over)
+            .add_systems(OnEnter(GameState::Paused), setup_pause)
+            .add_systems(OnExit(GameState::Paused), remove_pause)
+            .add_systems(OnEnter(GameState::Game), close_settings);
     }
 }
 
-#[derive(Resource)]
-struct UiResources {
-    languages: Vec<Handle<LocaleAsset>>,
-}
-
 fn setup_ui(
     mut cmd: Commands,
-    ui_res: Res<UiResources>,
     asset_serve: Res<AssetServer>,
-    lang: Res<LocaleLangs>,
-    languages: Res<Assets<LocaleAsset>>,
+    locale: Localizer,
+    config: Res<GameConfig>,
+    settings: Res<Settings>,
     mut runned: Local<bool>,
 ) {
     if *runned {
         return;
     }
+    let palette = Palette::of(&settings);
     let font_regular = asset_serve.load("fonts/Lato-Regular.ttf");
     let font_light = asset_serve.load("fonts/Lato-Light.ttf");
 
-    let Some(lang) = languages.get(&ui_res.languages[*lang as usize]) else { return; };
+    let Some(lang) = locale.bundle() else { return; };
     *runned = true;
 
     // 404 text
@@ -82,7 +138,7 @@ fn setup_ui(
                         TextStyle {
                             font: font_regular.clone(),
                             font_size: 128.,
-                            color: Color::rgba_u8(52, 52, 52, 255),
+                            color: palette.text,
                         },
                     ),
                     TextSection::new(
@@ -90,22 +146,22 @@ fn setup_ui(
                         TextStyle {
                             font: font_light.clone(),
                             font_size: 32.,
-                            color: Color::rgba_u8(52, 52, 52, 255),
+                            color: palette.text,
                         },
                     ),
                 ])
                 .with_alignment(TextAlignment::Center),
                 ..default()
             },
+            LocalizedText::new("message1").with_section(1),
+            Label,
+            ThemeColor::new(ThemeRole::Text),
             Animator::new(
-                Delay::new(Duration::from_secs(*TIME_WAIT_TO_START)).then(
+                Delay::new(Duration::from_secs(config.time_wait_to_start)).then(
                     Tween::new(
                         EaseFunction::QuadraticInOut,
                         Duration::from_secs(5),
-                        GameTextColorLens::create(
-                            Color::rgba_u8(52, 52, 52, 255),
-                            Color::rgba_u8(52, 52, 52, 0),
-                        ),
+                        ThemeAlphaLens::create(1., 0.),
                     )
                     .with_completed_event(1),
                 ),
@@ -113,10 +169,13 @@ fn setup_ui(
         ));
 
         // Come back to Home button
-        cmd.spawn(ButtonBundle {
-            background_color: BackgroundColor(Color::WHITE.with_a(0.)),
-            ..default()
-        })
+        cmd.spawn((
+            ButtonBundle {
+                background_color: BackgroundColor(Color::WHITE.with_a(0.)),
+                ..default()
+            },
+            ButtonAction::Home,
+        ))
         .with_children(|cmd| {
             cmd.spawn((
                 TextBundle {
@@ -125,27 +184,64 @@ fn setup_ui(
                         TextStyle {
                             font: font_light.clone(),
                             font_size: 32.,
-                            color: Color::rgba_u8(0, 133, 255, 255),
+                            color: palette.accent,
                         },
                     )
                     .with_alignment(TextAlignment::Center),
                     ..default()
                 },
+                LocalizedText::new("button"),
+                ThemeColor::new(ThemeRole::Accent),
                 Animator::new(
-                    Delay::new(Duration::from_secs(*TIME_WAIT_TO_START)).then(
+                    Delay::new(Duration::from_secs(config.time_wait_to_start)).then(
                         Tween::new(
                             EaseFunction::QuadraticInOut,
                             Duration::from_secs(5),
-                            GameTextColorLens::create(
-                                Color::rgba_u8(0, 133, 255, 255),
-                                Color::rgba_u8(0, 133, 255, 0),
-                            ),
+                            ThemeAlphaLens::create(1., 0.),
                         )
                         .with_completed_event(1),
                     ),
                 ),
             ));
         });
+
+        // Language picker
+        cmd.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                column_gap: Val::Px(20.),
+                margin: UiRect::top(Val::Px(20.)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|cmd| {
+            for entry in locale.languages() {
+                // fades with the home button, removed with the container
+                spawn_button_with(
+                    cmd,
+                    ButtonAction::Language(entry.tag.clone()),
+                    entry.name.clone(),
+                    &font_light,
+                    Animator::new(
+                        Delay::new(Duration::from_secs(config.time_wait_to_start)).then(
+                            Tween::new(
+                                EaseFunction::QuadraticInOut,
+                                Duration::from_secs(5),
+                                ThemeAlphaLens::create(1., 0.),
+                            ),
+                        ),
+                    ),
+                );
+            }
+        });
+        spawn_localized_button(
+            cmd,
+            ButtonAction::Settings,
+            lang.get_default("settings", "Ajustes").to_string(),
+            &font_light,
+            "settings",
+        );
     });
 
     // Transparent warning message
@@ -173,21 +269,21 @@ fn setup_ui(
                     TextStyle {
                         font: font_regular.clone(),
                         font_size: 32.,
-                        color: Color::rgba_u8(52, 52, 52, 45),
+                        color: palette.hint,
                     },
                 )
                 .with_alignment(TextAlignment::Center),
                 ..default()
             },
+            LocalizedText::new("message2"),
+            Label,
+            ThemeColor::new(ThemeRole::Hint),
             Animator::new(
-                Delay::new(Duration::from_secs(*TIME_WAIT_TO_START)).then(
+                Delay::new(Duration::from_secs(config.time_wait_to_start)).then(
                     Tween::new(
                         EaseFunction::QuadraticInOut,
                         Duration::from_secs(5),
-                        GameTextColorLens::create(
-                            Color::rgba_u8(52, 52, 52, 45),
-                            Color::rgba_u8(52, 52, 52, 0),
-                        ),
+                        ThemeAlphaLens::create(1., 0.),
                     )
                     .with_completed_event(1),
                 ),
@@ -219,48 +315,301 @@ fn setup_ui(
                     TextStyle {
                         font: font_light.clone(),
                         font_size: 128.,
-                        color: Color::rgba_u8(52, 52, 52, 0),
+                        color: Color::NONE,
                     },
                 )
                 .with_alignment(TextAlignment::Center),
                 ..default()
             },
             ScoreText,
+            AccessibilityNode(NodeBuilder::new(Role::StaticText)),
+            ThemeColor::transparent(ThemeRole::Text),
             Animator::new(
-                Delay::new(Duration::from_secs(*TIME_WAIT_TO_START + 3)).then(
+                Delay::new(Duration::from_secs(config.time_wait_to_start + 3)).then(
                     Tween::new(
                         EaseFunction::QuadraticInOut,
                         Duration::from_secs(5),
-                        GameTextColorLens::create(
-                            Color::rgba_u8(52, 52, 52, 0),
-                            Color::rgba_u8(52, 52, 52, 255),
-                        ),
+                        ThemeAlphaLens::create(0., 1.),
                     )
                     .with_completed_event(2),
                 ),
             ),
         ));
+        // Best Score Text
+        cmd.spawn((
+            TextBundle {
+                text: Text::from_sections([
+                    TextSection::new(
+                        lang.get_default("best", "Mejor"),
+                        TextStyle {
+                            font: font_light.clone(),
+                            font_size: 32.,
+                            color: Color::NONE,
+                        },
+                    ),
+                    TextSection::new(
+                        " ",
+                        TextStyle {
+                            font: font_light.clone(),
+                            font_size: 32.,
+                            color: Color::NONE,
+                        },
+                    ),
+                    TextSection::new(
+                        "0",
+                        TextStyle {
+                            font: font_regular.clone(),
+                            font_size: 32.,
+                            color: Color::NONE,
+                        },
+                    ),
+                ])
+                .with_alignment(TextAlignment::Center),
+                ..default()
+            },
+            BestScoreText,
+            LocalizedText::new("best"),
+            ThemeColor::transparent(ThemeRole::TextDim),
+            Animator::new(
+                Delay::new(Duration::from_secs(config.time_wait_to_start + 3)).then(Tween::new(
+                    EaseFunction::QuadraticInOut,
+                    Duration::from_secs(5),
+                    ThemeAlphaLens::create(0., 1.),
+                )),
+            ),
+        ));
+        // Lives or Time Text
+        let key = match config.mode {
+            GameMode::Endless => return,
+            GameMode::Lives(_) => "lives",
+            GameMode::Timed(_) => "time",
+        };
+        cmd.spawn((
+            TextBundle {
+                text: Text::from_sections([
+                    TextSection::new(
+                        lang.get_default(key, key),
+                        TextStyle {
+                            font: font_light.clone(),
+                            font_size: 32.,
+                            color: Color::NONE,
+                        },
+                    ),
+                    TextSection::new(
+                        " ",
+                        TextStyle {
+                            font: font_light.clone(),
+                            font_size: 32.,
+                            color: Color::NONE,
+                        },
+                    ),
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font: font_regular.clone(),
+                            font_size: 32.,
+                            color: Color::NONE,
+                        },
+                    ),
+                ])
+                .with_alignment(TextAlignment::Center),
+                ..default()
+            },
+            RoundText,
+            LocalizedText::new(key),
+            ThemeColor::transparent(ThemeRole::TextDim),
+            Animator::new(
+                Delay::new(Duration::from_secs(config.time_wait_to_start + 3)).then(Tween::new(
+                    EaseFunction::QuadraticInOut,
+                    Duration::from_secs(5),
+                    ThemeAlphaLens::create(0., 1.),
+                )),
+            ),
+        ));
+    });
+}
+
+//
+// Full screen layer over the game, for the pause and game over screens,
+// spawned with `ThemeRole::Overlay` to follow the palette
+//
+fn overlay() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            display: Display::Flex,
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            row_gap: Val::Px(10.),
+            ..default()
+        },
+        background_color: BackgroundColor(Palette::DEFAULT.overlay),
+        z_index: ZIndex::Global(10),
+        ..default()
+    }
+}
+
+/// Text button of the overlays, the press is handled by [`button_system`]
+fn spawn_button(cmd: &mut ChildBuilder, action: ButtonAction, label: String, font: &Handle<Font>) {
+    spawn_button_with(cmd, action, label, font, ());
+}
+
+/// Same of [`spawn_button`], the label follows the active language
+fn spawn_localized_button(
+    cmd: &mut ChildBuilder,
+    action: ButtonAction,
+    label: String,
+    font: &Handle<Font>,
+    key: &str,
+) {
+    spawn_button_with(cmd, action, label, font, LocalizedText::new(key));
+}
+
+fn spawn_button_with(
+    cmd: &mut ChildBuilder,
+    action: ButtonAction,
+    label: String,
+    font: &Handle<Font>,
+    extra: impl Bundle,
+) {
+    cmd.spawn((
+        ButtonBundle {
+            background_color: BackgroundColor(Color::WHITE.with_a(0.)),
+            ..default()
+        },
+        action,
+    ))
+    .with_children(|cmd| {
+        cmd.spawn((
+            TextBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 32.,
+                        color: Palette::DEFAULT.accent,
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                ..default()
+            },
+            ThemeColor::new(ThemeRole::Accent),
+            extra,
+        ));
     });
 }
 
 fn button_system(
     mut window: Query<&mut Window, With<PrimaryWindow>>,
-    mut interaction_query: Query<(&Interaction, &Children), (Changed<Interaction>, With<Button>)>,
+    mut interaction_query: Query<
+        (&Interaction, &ButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
     url_callback: Res<OpenLinkResource>,
+    config: Res<GameConfig>,
+    mut settings: ResMut<Settings>,
+    mut panel: ResMut<SettingsPanel>,
+    mut restart: EventWriter<RestartEvent>,
+    mut pause: EventWriter<PauseEvent>,
 ) {
-    let mut window = window.single_mut();
-    for (interaction, _children) in &mut interaction_query {
-        match *interaction {
+    for (interaction, action) in &mut interaction_query {
+        let icon = match *interaction {
             Interaction::Pressed => {
-                window.cursor.icon = CursorIcon::Hand;
-                url_callback.0(HOME_URL);
+                match action {
+                    ButtonAction::Home => url_callback.0(&config.home_url),
+                    ButtonAction::Restart => restart.send(RestartEvent),
+                    ButtonAction::Resume => pause.send(PauseEvent::Resume),
+                    ButtonAction::Language(tag) => settings.language = Some(tag.clone()),
+                    ButtonAction::Settings => panel.open = true,
+                    ButtonAction::CloseSettings => panel.open = false,
+                    ButtonAction::Volume(bus, steps) => settings.change_volume(*bus, *steps),
+                    ButtonAction::Fullscreen => settings.fullscreen = !settings.fullscreen,
+                    ButtonAction::ReducedMotion => {
+                        settings.reduced_motion = !settings.reduced_motion
+                    }
+                    ButtonAction::HitboxScale(steps) => settings.change_hitbox_scale(*steps),
+                    ButtonAction::SpeedScale(steps) => settings.change_speed_scale(*steps),
+                    ButtonAction::HighContrast => settings.high_contrast = !settings.high_contrast,
+                }
+                CursorIcon::Hand
+            }
+            Interaction::Hovered => CursorIcon::Hand,
+            Interaction::None => CursorIcon::Default,
+        };
+        if let Ok(mut window) = window.get_single_mut() {
+            window.cursor.icon = icon;
+        }
+    }
+}
+
+//
+// Confirm and back actions on the overlays, for the keyboard and gamepads
+//
+fn overlay_actions(
+    state: Res<State<GameState>>,
+    mut panel: ResMut<SettingsPanel>,
+    mut actions: EventReader<ActionEvent>,
+    mut restart: EventWriter<RestartEvent>,
+    mut pause: EventWriter<PauseEvent>,
+) {
+    for event in actions.iter() {
+        match (event.action, state.get()) {
+            (GameAction::Back, _) if panel.open => panel.open = false,
+            (GameAction::Confirm, _) if panel.open => {}
+            (GameAction::Confirm | GameAction::Back, GameState::Paused) => {
+                pause.send(PauseEvent::Resume)
+            }
+            (GameAction::Confirm, GameState::GameOver) => restart.send(RestartEvent),
+            _ => {}
+        }
+    }
+}
+
+//
+// The picker shows the active language as plain text
+//
+fn language_highlight(
+    locale: Localizer,
+    buttons: Query<(&ButtonAction, &Children)>,
+    mut themes: Query<&mut ThemeColor>,
+) {
+    let active = locale.locale().active();
+    for (action, children) in buttons.iter() {
+        let ButtonAction::Language(tag) = action else { continue; };
+        let role = if Some(tag) == active {
+            ThemeRole::Text
+        } else {
+            ThemeRole::Accent
+        };
+        for child in children.iter() {
+            let Ok(mut theme) = themes.get_mut(*child) else { continue; };
+            if theme.role != role {
+                theme.role = role;
             }
-            Interaction::Hovered => window.cursor.icon = CursorIcon::Hand,
-            Interaction::None => window.cursor.icon = CursorIcon::Default,
         }
     }
 }
 
+//
+// Screen readers get the score with its label, the text is only the number
+//
+fn score_accessibility(
+    locale: Localizer,
+    mut texts: Query<(&Text, &mut AccessibilityNode), (With<ScoreText>, Changed<Text>)>,
+) {
+    let lang = locale.bundle();
+    let label = lang
+        .as_ref()
+        .and_then(|lang| lang.get("score"))
+        .unwrap_or("Puntaje");
+    for (text, mut node) in texts.iter_mut() {
+        node.set_name(format!("{label}: {}", text.sections[0].value));
+    }
+}
+
 fn run_if_anim(
     anim_reader: EventReader<TweenCompleted>,
     texts: Query<Entity, (With<Node>, With<RemovableUI>)>,
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+
+use crate::{
+    game::{Round, ScoreTextResource},
+    locale::{LocaleArgs, Localizer},
+    settings::Settings,
+    stats::{Accuracy, PlayerStats},
+};
+
+use super::{overlay, spawn_button, ButtonAction, Palette, ThemeColor, ThemeRole};
+
+#[derive(Component)]
+pub(super) struct GameOverUI;
+
+//
+// Results of the round, with buttons to play again or go home
+//
+pub(super) fn setup_game_over(
+    mut cmd: Commands,
+    asset_serve: Res<AssetServer>,
+    locale: Localizer,
+    score: Res<ScoreTextResource>,
+    round: Res<Round>,
+    accuracy: Res<Accuracy>,
+    stats: Res<PlayerStats>,
+    settings: Res<Settings>,
+) {
+    let font_regular = asset_serve.load("fonts/Lato-Regular.ttf");
+    let font_light = asset_serve.load("fonts/Lato-Light.ttf");
+    let lang = locale.bundle();
+    let text = |key: &str, default: &str| {
+        lang.as_ref()
+            .and_then(|lang| lang.get(key))
+            .unwrap_or(default)
+            .to_string()
+    };
+
+    let style = |font: &Handle<Font>, font_size: f32, color: Color| TextStyle {
+        font: font.clone(),
+        font_size,
+        color,
+    };
+    // the labels and values are on the same text, so are colored here
+    let palette = Palette::of(&settings);
+    let (dark, light) = (palette.text, palette.text_dim);
+    let best = stats.best_score.max(score.0);
+
+    cmd.spawn((overlay(), ThemeColor::new(ThemeRole::Overlay), GameOverUI))
+        .with_children(|cmd| {
+            cmd.spawn(TextBundle {
+                text: Text::from_section(
+                    if round.cleared {
+                        text("level_complete", "Nivel completado")
+                    } else {
+                        text("game_over", "Fin del juego")
+                    },
+                    style(&font_regular, 96., dark),
+                )
+                .with_alignment(TextAlignment::Center),
+                ..default()
+            });
+            for (label, value) in [
+                (
+                    text("score", "Puntaje"),
+                    lang.as_ref()
+                        .and_then(|lang| {
+                            lang.format("points", &LocaleArgs::new().with("score", score.0))
+                        })
+                        .unwrap_or_else(|| score.0.to_string()),
+                ),
+                (
+                    text("accuracy", "Precisión"),
+                    format!("{:.0}%", accuracy.ratio() * 100.),
+                ),
+                (text("best", "Mejor"), best.to_string()),
+            ] {
+                cmd.spawn(TextBundle {
+                    text: Text::from_sections([
+                        TextSection::new(format!("{label} "), style(&font_light, 32., light)),
+                        TextSection::new(value, style(&font_regular, 32., dark)),
+                    ])
+                    .with_alignment(TextAlignment::Center),
+                    ..default()
+                });
+            }
+            for (action, label) in [
+                (ButtonAction::Restart, text("restart", "Jugar de nuevo")),
+                (ButtonAction::Home, text("button", "Volver al Inicio")),
+            ] {
+                spawn_button(cmd, action, label, &font_light);
+            }
+        });
+}
+
+pub(super) fn remove_game_over(mut cmd: Commands, screens: Query<Entity, With<GameOverUI>>) {
+    for screen in screens.iter() {
+        cmd.entity(screen).despawn_recursive();
+    }
+}
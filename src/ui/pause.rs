@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::locale::Localizer;
+
+use super::{overlay, spawn_button, ButtonAction, Palette, ThemeColor, ThemeRole};
+
+#[derive(Component)]
+pub(super) struct PauseUI;
+
+pub(super) fn setup_pause(mut cmd: Commands, asset_serve: Res<AssetServer>, locale: Localizer) {
+    let font_regular = asset_serve.load("fonts/Lato-Regular.ttf");
+    let font_light = asset_serve.load("fonts/Lato-Light.ttf");
+    let lang = locale.bundle();
+    let text = |key: &str, default: &str| {
+        lang.as_ref()
+            .and_then(|lang| lang.get(key))
+            .unwrap_or(default)
+            .to_string()
+    };
+
+    cmd.spawn((overlay(), ThemeColor::new(ThemeRole::Overlay), PauseUI))
+        .with_children(|cmd| {
+            cmd.spawn((
+                TextBundle {
+                    text: Text::from_section(
+                        text("paused", "Pausa"),
+                        TextStyle {
+                            font: font_regular.clone(),
+                            font_size: 96.,
+                            color: Palette::DEFAULT.text,
+                        },
+                    )
+                    .with_alignment(TextAlignment::Center),
+                    ..default()
+                },
+                ThemeColor::new(ThemeRole::Text),
+            ));
+            spawn_button(
+                cmd,
+                ButtonAction::Resume,
+                text("resume", "Continuar"),
+                &font_light,
+            );
+            spawn_button(
+                cmd,
+                ButtonAction::Settings,
+                text("settings", "Ajustes"),
+                &font_light,
+            );
+            spawn_button(cmd, ButtonAction::Home, text("quit", "Salir"), &font_light);
+        });
+}
+
+pub(super) fn remove_pause(mut cmd: Commands, screens: Query<Entity, With<PauseUI>>) {
+    for screen in screens.iter() {
+        cmd.entity(screen).despawn_recursive();
+    }
+}
@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+
+use crate::{
+    audio::AudioBus,
+    locale::{LocalizedText, Localizer},
+    settings::Settings,
+};
+
+use super::{
+    overlay, spawn_button, spawn_localized_button, ButtonAction, Palette, ThemeColor, ThemeRole,
+};
+
+//
+// The settings panel is shown over the main page or the pause screen
+//
+#[derive(Resource, Default)]
+pub(super) struct SettingsPanel {
+    pub(super) open: bool,
+}
+
+#[derive(Component)]
+pub(super) struct SettingsUI;
+
+/// Percentage printed between the `-` and `+` buttons
+#[derive(Component, Clone, Copy)]
+pub(super) enum ValueText {
+    Volume(AudioBus),
+    Hitbox,
+    Speed,
+}
+
+pub(super) fn toggle_settings(
+    mut cmd: Commands,
+    asset_serve: Res<AssetServer>,
+    locale: Localizer,
+    panel: Res<SettingsPanel>,
+    screens: Query<Entity, With<SettingsUI>>,
+) {
+    for screen in screens.iter() {
+        cmd.entity(screen).despawn_recursive();
+    }
+    if !panel.open {
+        return;
+    }
+    let font_regular = asset_serve.load("fonts/Lato-Regular.ttf");
+    let font_light = asset_serve.load("fonts/Lato-Light.ttf");
+    let lang = locale.bundle();
+    let text = |key: &str, default: &str| {
+        lang.as_ref()
+            .and_then(|lang| lang.get(key))
+            .unwrap_or(default)
+            .to_string()
+    };
+
+    let label = |key: &str, default: &str| {
+        (
+            TextBundle::from_section(
+                text(key, default),
+                TextStyle {
+                    font: font_light.clone(),
+                    font_size: 32.,
+                    color: Palette::DEFAULT.text_dim,
+                },
+            ),
+            LocalizedText::new(key),
+            ThemeColor::new(ThemeRole::TextDim),
+        )
+    };
+    let row = || NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(20.),
+            ..default()
+        },
+        ..default()
+    };
+
+    cmd.spawn((
+        NodeBundle {
+            // over the pause screen
+            z_index: ZIndex::Global(20),
+            ..overlay()
+        },
+        ThemeColor::new(ThemeRole::Overlay),
+        SettingsUI,
+    ))
+    .with_children(|cmd| {
+        cmd.spawn((
+            TextBundle::from_section(
+                text("settings", "Ajustes"),
+                TextStyle {
+                    font: font_regular.clone(),
+                    font_size: 96.,
+                    color: Palette::DEFAULT.text,
+                },
+            ),
+            LocalizedText::new("settings"),
+            ThemeColor::new(ThemeRole::Text),
+        ));
+        for (value, key, default) in [
+            (ValueText::Volume(AudioBus::Master), "volume", "Volumen"),
+            (ValueText::Volume(AudioBus::Music), "music", "Música"),
+            (ValueText::Volume(AudioBus::Sfx), "effects", "Efectos"),
+            (ValueText::Hitbox, "hitbox_size", "Área de golpe"),
+            (ValueText::Speed, "bug_speed", "Velocidad de los bichos"),
+        ] {
+            let step = |steps| match value {
+                ValueText::Volume(bus) => ButtonAction::Volume(bus, steps),
+                ValueText::Hitbox => ButtonAction::HitboxScale(steps),
+                ValueText::Speed => ButtonAction::SpeedScale(steps),
+            };
+            cmd.spawn(row()).with_children(|cmd| {
+                cmd.spawn(label(key, default));
+                spawn_button(cmd, step(-1), "-".to_string(), &font_regular);
+                cmd.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: font_regular.clone(),
+                            font_size: 32.,
+                            color: Palette::DEFAULT.text,
+                        },
+                    ),
+                    ThemeColor::new(ThemeRole::Text),
+                    value,
+                ));
+                spawn_button(cmd, step(1), "+".to_string(), &font_regular);
+            });
+        }
+        for (action, key, default) in [
+            (ButtonAction::Fullscreen, "fullscreen", "Pantalla completa"),
+            (
+                ButtonAction::ReducedMotion,
+                "reduced_motion",
+                "Reducir movimiento",
+            ),
+            (
+                ButtonAction::HighContrast,
+                "high_contrast",
+                "Alto contraste",
+            ),
+        ] {
+            cmd.spawn(row()).with_children(|cmd| {
+                cmd.spawn(label(key, default));
+                // the value is printed by `settings_values_print`
+                spawn_button(cmd, action, String::new(), &font_regular);
+            });
+        }
+        cmd.spawn(row()).with_children(|cmd| {
+            cmd.spawn(label("language", "Idioma"));
+            for entry in locale.languages() {
+                spawn_button(
+                    cmd,
+                    ButtonAction::Language(entry.tag.clone()),
+                    entry.name.clone(),
+                    &font_light,
+                );
+            }
+        });
+        spawn_localized_button(
+            cmd,
+            ButtonAction::CloseSettings,
+            text("back", "Volver"),
+            &font_light,
+            "back",
+        );
+    });
+}
+
+pub(super) fn close_settings(mut panel: ResMut<SettingsPanel>) {
+    if panel.open {
+        panel.open = false;
+    }
+}
+
+//
+// Current values of the settings, shown on the panel
+//
+pub(super) fn settings_values_print(
+    settings: Res<Settings>,
+    locale: Localizer,
+    buttons: Query<(&ButtonAction, &Children)>,
+    mut values: Query<(&mut Text, &ValueText)>,
+    mut texts: Query<&mut Text, Without<ValueText>>,
+) {
+    let lang = locale.bundle();
+    let switch = |on: bool| {
+        let (key, default) = if on {
+            ("on", "Activado")
+        } else {
+            ("off", "Desactivado")
+        };
+        lang.as_ref()
+            .and_then(|lang| lang.get(key))
+            .unwrap_or(default)
+            .to_string()
+    };
+
+    for (mut text, value) in values.iter_mut() {
+        let value = match value {
+            ValueText::Volume(bus) => settings.volume(*bus),
+            ValueText::Hitbox => settings.hitbox_scale,
+            ValueText::Speed => settings.speed_scale,
+        };
+        set_value(&mut text, format!("{:.0}%", value * 100.));
+    }
+    for (action, children) in buttons.iter() {
+        let value = match action {
+            ButtonAction::Fullscreen => switch(settings.fullscreen),
+            ButtonAction::ReducedMotion => switch(settings.reduced_motion),
+            ButtonAction::HighContrast => switch(settings.high_contrast),
+            _ => continue,
+        };
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                set_value(&mut text, value.clone());
+            }
+        }
+    }
+}
+
+// Avoid to flag the text as changed every frame
+fn set_value(text: &mut Mut<Text>, value: String) {
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::{core_pipeline::clear_color::ClearColorConfig, prelude::*};
+
+use crate::settings::Settings;
+
+//
+// Colors of the UI, the high contrast one drops the translucent greys
+//
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Palette {
+    /// Titles, scores and values
+    pub text: Color,
+    /// Labels and secondary texts
+    pub text_dim: Color,
+    /// Messages hidden on purpose, like the patience one
+    pub hint: Color,
+    /// Buttons, the crosshair and the combo points
+    pub accent: Color,
+    /// Background of the pause, game over and settings screens
+    pub overlay: Color,
+    /// Clear color of the camera
+    pub background: Color,
+}
+
+impl Palette {
+    pub const DEFAULT: Self = Self {
+        text: Color::rgba(52. / 255., 52. / 255., 52. / 255., 1.),
+        text_dim: Color::rgba(52. / 255., 52. / 255., 52. / 255., 160. / 255.),
+        hint: Color::rgba(52. / 255., 52. / 255., 52. / 255., 45. / 255.),
+        accent: Color::rgba(0., 133. / 255., 1., 1.),
+        overlay: Color::rgba(227. / 255., 227. / 255., 227. / 255., 230. / 255.),
+        background: Color::rgb(227. / 255., 227. / 255., 227. / 255.),
+    };
+
+    pub const HIGH_CONTRAST: Self = Self {
+        text: Color::BLACK,
+        text_dim: Color::BLACK,
+        hint: Color::BLACK,
+        accent: Color::rgb(0., 64. / 255., 160. / 255.),
+        overlay: Color::WHITE,
+        background: Color::WHITE,
+    };
+
+    pub fn of(settings: &Settings) -> Self {
+        if settings.high_contrast {
+            Self::HIGH_CONTRAST
+        } else {
+            Self::DEFAULT
+        }
+    }
+
+    pub fn color(&self, role: ThemeRole) -> Color {
+        match role {
+            ThemeRole::Text => self.text,
+            ThemeRole::TextDim => self.text_dim,
+            ThemeRole::Hint => self.hint,
+            ThemeRole::Accent => self.accent,
+            ThemeRole::Overlay => self.overlay,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ThemeRole {
+    Text,
+    TextDim,
+    Hint,
+    Accent,
+    Overlay,
+}
+
+//
+// Color of a text or node taken from the palette, the fades animate
+// the alpha so the texts follow the palette while fade in or out
+//
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ThemeColor {
+    pub role: ThemeRole,
+    /// Fraction of the alpha of the role
+    pub alpha: f32,
+}
+
+impl ThemeColor {
+    pub fn new(role: ThemeRole) -> Self {
+        Self { role, alpha: 1. }
+    }
+
+    pub fn transparent(role: ThemeRole) -> Self {
+        Self { role, alpha: 0. }
+    }
+
+    fn color(&self, palette: &Palette) -> Color {
+        let color = palette.color(self.role);
+        color.with_a(color.a() * self.alpha)
+    }
+}
+
+pub(super) fn apply_theme(
+    settings: Res<Settings>,
+    mut cameras: Query<&mut Camera3d>,
+    mut texts: Query<(Ref<ThemeColor>, &mut Text)>,
+    mut nodes: Query<
+        (
+            Ref<ThemeColor>,
+            Option<&mut BackgroundColor>,
+            Option<&mut BorderColor>,
+        ),
+        Without<Text>,
+    >,
+) {
+    let palette = Palette::of(&settings);
+    let changed = settings.is_changed();
+    if changed {
+        for mut camera in cameras.iter_mut() {
+            camera.clear_color = ClearColorConfig::Custom(palette.background);
+        }
+    }
+    for (theme, mut text) in texts.iter_mut() {
+        if !changed && !theme.is_changed() {
+            continue;
+        }
+        let color = theme.color(&palette);
+        for section in text.sections.iter_mut() {
+            section.style.color = color;
+        }
+    }
+    for (theme, background, border) in nodes.iter_mut() {
+        if !changed && !theme.is_changed() {
+            continue;
+        }
+        let color = theme.color(&palette);
+        if let Some(mut background) = background {
+            background.0 = color;
+        }
+        if let Some(mut border) = border {
+            border.0 = color;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn faded_colors_keep_the_role_alpha() {
+        let mut theme = ThemeColor::new(ThemeRole::Hint);
+        assert_eq!(theme.color(&Palette::DEFAULT), Palette::DEFAULT.hint);
+        theme.alpha = 0.5;
+        assert_eq!(
+            theme.color(&Palette::DEFAULT).a(),
+            Palette::DEFAULT.hint.a() / 2.
+        );
+        assert_eq!(theme.color(&Palette::HIGH_CONTRAST).a(), 0.5);
+    }
+}